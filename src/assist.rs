@@ -0,0 +1,101 @@
+use raylib::prelude::Vector2;
+
+use crate::caster::Sprite;
+use crate::entity::{SpriteSheet, SpriteTex};
+use crate::maze::{Connectivity, Maze};
+
+// Ayudas para no perderse (opción "Ayudas para orientarse"):
+//  - `Trail`: migas donde pasó el jugador, en el piso y en el mapa. Las deja
+//    la simulación, así la repetición las vuelve a dejar igual.
+//  - `Hint`: el camino más corto hasta la salida más cercana (cualquier tile con
+//    `exit`), que el mapa muestra unos segundos. Se pide con la entrada
+//    (`InputSnapshot::hint_pressed`) y vive en `Sim::hint`: se borra al
+//    reiniciar y la repetición la vuelve a mostrar. `Replay::hints` las cuenta.
+
+// no se deja una miga si ya hay otra a menos de esto (celdas)
+const CRUMB_SPACING: f32 = 0.8;
+// pasado el tope se borran las más viejas
+const MAX_CRUMBS: usize = 400;
+// alto del sprite en el piso, relativo a una pared
+const CRUMB_SCALE: f32 = 0.06;
+
+/// Cuánto dura una pista en pantalla (segundos de juego); el último se desvanece
+pub const HINT_SECONDS: f32 = 5.0;
+
+#[derive(Clone, Debug, Default)]
+pub struct Trail {
+    crumbs: Vec<Vector2>, // píxeles de mundo, de la más vieja a la más nueva
+}
+
+impl Trail {
+    /// Deja una miga en `pos` si por ahí todavía no hay ninguna
+    pub fn drop_at(&mut self, maze: &Maze, pos: Vector2) {
+        let spacing = CRUMB_SPACING * maze.block_size() as f32;
+        if self.crumbs.iter().rev().any(|&c| (c - pos).length() < spacing) {
+            return;
+        }
+        if self.crumbs.len() == MAX_CRUMBS {
+            self.crumbs.remove(0);
+        }
+        self.crumbs.push(pos);
+    }
+
+    pub fn crumbs(&self) -> &[Vector2] {
+        &self.crumbs
+    }
+
+    /// Una piedrita por miga, para `render_3d_scene`
+    pub fn sprites<'a>(&self, sheet: &'a SpriteSheet) -> Vec<Sprite<'a>> {
+        let tex = sheet.get(SpriteTex::Crumb);
+        self.crumbs.iter().map(|&pos| Sprite { pos, tex, scale: CRUMB_SCALE }).collect()
+    }
+}
+
+/// Camino a la salida pedido en el segundo `shown_at` del nivel (`Sim::time`)
+#[derive(Clone, Debug)]
+pub struct Hint {
+    path: Vec<Vector2>, // centros de celda en píxeles de mundo, hasta la salida
+    shown_at: f32,
+}
+
+impl Hint {
+    /// None si desde `from` no se llega a ninguna salida.
+    /// Sin caché: se calcula solo cuando el jugador pide una pista.
+    pub fn to_exit(maze: &Maze, from: Vector2, now: f32) -> Option<Self> {
+        let field = maze.distance_field(|t| t.def().exit, Connectivity::Eight);
+        let cells = field.path_from(maze, maze.world_to_cell(from))?;
+        let path = cells.into_iter().map(|c| maze.cell_center_world(c)).collect();
+        Some(Self { path, shown_at: now })
+    }
+
+    pub fn path(&self) -> &[Vector2] {
+        &self.path
+    }
+
+    /// Opacidad 0..1 en el instante `now`; 0 = ya no se muestra
+    pub fn alpha(&self, now: f32) -> f32 {
+        let t = now - self.shown_at;
+        if !(0.0..HINT_SECONDS).contains(&t) {
+            return 0.0;
+        }
+        (HINT_SECONDS - t).min(1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hint_leads_to_the_exit_and_fades() {
+        let maze = Maze::parse("######\n#P # #\n#  #D#\n#    #\n######", 64);
+        let from = maze.cell_center_world((1, 1));
+        let hint = Hint::to_exit(&maze, from, 2.0).unwrap();
+        assert_eq!(hint.path().first(), Some(&from));
+        assert_eq!(hint.path().last(), Some(&maze.cell_center_world((4, 2))));
+
+        assert_eq!(hint.alpha(1.0), 0.0);
+        assert_eq!(hint.alpha(3.0), 1.0);
+        assert_eq!(hint.alpha(2.0 + HINT_SECONDS), 0.0);
+    }
+}
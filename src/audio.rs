@@ -0,0 +1,314 @@
+use std::ffi::CString;
+use std::path::Path;
+
+use raylib::ffi;
+use raylib::prelude::Vector2;
+
+use crate::maze::Maze;
+use crate::screamer::has_los;
+
+// Audio del juego: dueño del dispositivo, los efectos y la música en stream.
+// Todo el `unsafe` de raylib queda acá. Si no hay dispositivo (CI, máquina sin
+// placa de sonido, `--no-audio`) o falta un archivo, se avisa una vez por
+// stderr y las llamadas siguen funcionando sin sonar.
+//
+// Los sonidos del mundo (pasos de las entidades, el screamer) se tocan con
+// `play_at` y un `Spatial`: bajan con la distancia, se panean según de qué
+// lado del jugador vienen y suenan apagados si hay paredes en el medio.
+
+// distancias en celdas: más cerca que NEAR suena entero, más lejos que FAR no suena
+const NEAR: f32 = 1.0;
+const FAR: f32 = 12.0;
+const MUFFLED_GAIN: f32 = 0.35;
+// raylib no tiene filtros por sonido: un tono un poco más grave hace de "apagado"
+const MUFFLED_PITCH: f32 = 0.85;
+// 1.0 = algo a un costado deja de oírse del otro oído; menos es más natural
+const PAN_WIDTH: f32 = 0.8;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Category {
+    Music,
+    Sfx,
+    Ui,
+}
+
+/// Cómo se oye una fuente desde la posición del jugador
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Spatial {
+    pub gain: f32, // 0..1, multiplica el volumen del efecto
+    pub pan: f32,  // -1 izquierda .. 1 derecha
+    pub muffled: bool,
+}
+
+impl Spatial {
+    /// Sin posición: al centro y a volumen pleno (los sonidos del propio jugador)
+    pub const CENTER: Spatial = Spatial { gain: 1.0, pan: 0.0, muffled: false };
+
+    /// `listener` y `angle` son los del jugador (`player.pos`, `player.a`)
+    pub fn at(maze: &Maze, listener: Vector2, angle: f32, source: Vector2) -> Self {
+        let to = source - listener;
+        let len = to.length();
+        let dist = len / maze.block_size() as f32;
+        let falloff = 1.0 - ((dist - NEAR) / (FAR - NEAR)).clamp(0.0, 1.0);
+        // lado: proyección sobre la derecha del jugador (a + 90°, como el strafe)
+        let pan = if len > 0.0 { (to.y * angle.cos() - to.x * angle.sin()) / len } else { 0.0 };
+        let muffled = !has_los(maze, source, listener);
+        let gain = falloff * falloff * if muffled { MUFFLED_GAIN } else { 1.0 };
+        Self { gain, pan, muffled }
+    }
+}
+
+/// Efecto cargado con `Audio::load_sound`
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct SoundId(usize);
+
+struct SoundSlot {
+    name: String,
+    sound: Option<ffi::Sound>, // None: sin dispositivo o no se pudo cargar
+    category: Category,
+    base_volume: f32, // volumen propio del efecto, antes de la categoría
+}
+
+struct MusicSlot {
+    music: ffi::Music,
+    path: String,
+}
+
+pub struct Audio {
+    device: bool,
+    sounds: Vec<SoundSlot>,
+    music: Option<MusicSlot>,
+    volumes: [f32; 3], // por `Category`
+}
+
+impl Audio {
+    /// Abre el dispositivo; si no hay, queda igual que `Audio::disabled`
+    pub fn init() -> Self {
+        unsafe { ffi::InitAudioDevice(); }
+        let device = unsafe { ffi::IsAudioDeviceReady() };
+        if !device {
+            eprintln!("No hay dispositivo de audio: se sigue sin sonido");
+        }
+        Self::with_device(device)
+    }
+
+    /// Sin dispositivo: todo se acepta y nada suena
+    pub fn disabled() -> Self {
+        Self::with_device(false)
+    }
+
+    fn with_device(device: bool) -> Self {
+        Self { device, sounds: Vec::new(), music: None, volumes: [1.0; 3] }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.device
+    }
+
+    /// Carga un efecto. Si el archivo falta el id sigue siendo válido (no suena).
+    pub fn load_sound(&mut self, name: &str, path: &str, category: Category, base_volume: f32) -> SoundId {
+        let sound = if self.device { load_sound_file(path) } else { None };
+        let slot = SoundSlot { name: name.to_string(), sound, category, base_volume };
+        self.apply_volume(&slot);
+        self.sounds.push(slot);
+        SoundId(self.sounds.len() - 1)
+    }
+
+    /// Id de un efecto por el nombre con que se cargó
+    pub fn sound(&self, name: &str) -> Option<SoundId> {
+        self.sounds.iter().position(|s| s.name == name).map(SoundId)
+    }
+
+    pub fn play(&self, id: SoundId) {
+        self.play_at(id, Spatial::CENTER);
+    }
+
+    /// Toca un efecto con volumen, paneo y tono según `at`; si no llega a oírse no hace nada
+    pub fn play_at(&self, id: SoundId, at: Spatial) {
+        let slot = &self.sounds[id.0];
+        let Some(sound) = slot.sound else { return; };
+        if at.gain <= 0.0 {
+            return;
+        }
+        unsafe {
+            ffi::SetSoundVolume(sound, slot.base_volume * self.volume(slot.category) * at.gain);
+            // en raylib 0.5 es el centro y 1.0 solo el canal izquierdo
+            ffi::SetSoundPan(sound, 0.5 - at.pan * PAN_WIDTH * 0.5);
+            ffi::SetSoundPitch(sound, if at.muffled { MUFFLED_PITCH } else { 1.0 });
+            ffi::PlaySound(sound);
+        }
+    }
+
+    pub fn stop(&self, id: SoundId) {
+        if let Some(sound) = self.sounds[id.0].sound {
+            unsafe { ffi::StopSound(sound); }
+        }
+    }
+
+    pub fn volume(&self, category: Category) -> f32 {
+        self.volumes[category as usize]
+    }
+
+    /// Volumen 0..1 de una categoría; se aplica enseguida a lo que ya está cargado
+    pub fn set_volume(&mut self, category: Category, volume: f32) {
+        self.volumes[category as usize] = volume.clamp(0.0, 1.0);
+        for slot in self.sounds.iter().filter(|s| s.category == category) {
+            self.apply_volume(slot);
+        }
+        if category == Category::Music
+            && let Some(m) = &self.music
+        {
+            unsafe { ffi::SetMusicVolume(m.music, self.volume(Category::Music)); }
+        }
+    }
+
+    // ---- música (un stream a la vez) ----
+
+    /// Arranca `path` en loop; si ya era la pista cargada no se vuelve a leer del disco
+    pub fn play_music(&mut self, path: &str) {
+        if !self.device {
+            return;
+        }
+        if self.music.as_ref().is_none_or(|m| m.path != path) {
+            self.unload_music();
+            self.music = load_music_file(path).map(|music| MusicSlot { music, path: path.to_string() });
+        }
+        if let Some(m) = &self.music {
+            unsafe {
+                ffi::SetMusicVolume(m.music, self.volume(Category::Music));
+                ffi::PlayMusicStream(m.music);
+            }
+        }
+    }
+
+    /// Una vez por frame mientras la música suena (rellena el buffer del stream)
+    pub fn update_music(&self) {
+        if let Some(m) = &self.music {
+            unsafe { ffi::UpdateMusicStream(m.music); }
+        }
+    }
+
+    pub fn pause_music(&self) {
+        if let Some(m) = &self.music {
+            unsafe { ffi::PauseMusicStream(m.music); }
+        }
+    }
+
+    pub fn resume_music(&self) {
+        if let Some(m) = &self.music {
+            unsafe { ffi::ResumeMusicStream(m.music); }
+        }
+    }
+
+    pub fn stop_music(&self) {
+        if let Some(m) = &self.music {
+            unsafe { ffi::StopMusicStream(m.music); }
+        }
+    }
+
+    fn unload_music(&mut self) {
+        if let Some(m) = self.music.take() {
+            unsafe { ffi::UnloadMusicStream(m.music); }
+        }
+    }
+
+    fn apply_volume(&self, slot: &SoundSlot) {
+        if let Some(sound) = slot.sound {
+            unsafe { ffi::SetSoundVolume(sound, slot.base_volume * self.volume(slot.category)); }
+        }
+    }
+}
+
+impl Drop for Audio {
+    fn drop(&mut self) {
+        for slot in self.sounds.drain(..) {
+            if let Some(sound) = slot.sound {
+                unsafe { ffi::UnloadSound(sound); }
+            }
+        }
+        self.unload_music();
+        if self.device {
+            unsafe { ffi::CloseAudioDevice(); }
+        }
+    }
+}
+
+fn load_sound_file(path: &str) -> Option<ffi::Sound> {
+    let c_path = c_path(path)?;
+    let sound = unsafe { ffi::LoadSound(c_path.as_ptr()) };
+    if unsafe { ffi::IsSoundValid(sound) } {
+        Some(sound)
+    } else {
+        eprintln!("No se pudo cargar {} (se sigue sin ese sonido)", path);
+        None
+    }
+}
+
+fn load_music_file(path: &str) -> Option<ffi::Music> {
+    let c_path = c_path(path)?;
+    let music = unsafe { ffi::LoadMusicStream(c_path.as_ptr()) };
+    if unsafe { ffi::IsMusicValid(music) } {
+        Some(music)
+    } else {
+        eprintln!("No se pudo cargar la música {}", path);
+        None
+    }
+}
+
+// raylib no distingue "no existe" de "formato inválido"; el aviso sí
+fn c_path(path: &str) -> Option<CString> {
+    if !Path::new(path).is_file() {
+        eprintln!("Falta {} (se sigue sin ese sonido)", path);
+        return None;
+    }
+    CString::new(path).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::f32::consts::PI;
+
+    // dos pasillos separados por una pared; el jugador en (1,2)
+    fn halls() -> (Maze, Vector2) {
+        let wall = "#".repeat(18);
+        let open = format!("#{}#", " ".repeat(16));
+        let maze = Maze::parse(&[&*wall, &open, &open, &wall, &open, &wall].join("\n"), 64);
+        let listener = maze.cell_center_world((1, 2));
+        (maze, listener)
+    }
+
+    #[test]
+    fn source_on_the_left_pans_left() {
+        let (maze, listener) = halls();
+        let north = maze.cell_center_world((1, 1));
+        // mirando al este el norte queda a la izquierda; mirando al oeste, a la derecha
+        assert!(Spatial::at(&maze, listener, 0.0, north).pan < -0.9);
+        assert!(Spatial::at(&maze, listener, PI, north).pan > 0.9);
+        // justo adelante: al centro
+        let ahead = maze.cell_center_world((5, 2));
+        assert!(Spatial::at(&maze, listener, 0.0, ahead).pan.abs() < 1e-5);
+    }
+
+    #[test]
+    fn past_max_range_is_silent() {
+        let (maze, listener) = halls();
+        let far = Spatial::at(&maze, listener, 0.0, maze.cell_center_world((16, 2)));
+        assert_eq!(far.gain, 0.0);
+        let near = Spatial::at(&maze, listener, 0.0, maze.cell_center_world((2, 2)));
+        assert_eq!(near.gain, 1.0);
+    }
+
+    #[test]
+    fn wall_in_between_muffles() {
+        let (maze, listener) = halls();
+        let behind = Spatial::at(&maze, listener, 0.0, maze.cell_center_world((1, 4)));
+        assert!(behind.muffled);
+        let clear = Spatial::at(&maze, listener, 0.0, maze.cell_center_world((1, 1)));
+        assert!(!clear.muffled);
+        // misma distancia (dos celdas): la pared baja la ganancia
+        let open = Spatial::at(&maze, listener, 0.0, maze.cell_center_world((3, 2)));
+        assert!(!open.muffled);
+        assert!((behind.gain - open.gain * MUFFLED_GAIN).abs() < 1e-5);
+    }
+}
@@ -0,0 +1,55 @@
+use std::path::PathBuf;
+use std::process::ExitCode;
+
+use proyecto_1::levels::level_files;
+use proyecto_1::validate::{check_level_file, Severity};
+
+// `cargo run --bin lint-levels [ARCHIVO|CARPETA ...]` (por defecto ./levels).
+// Sale con código 1 si algún nivel tiene errores; los avisos no cuentan.
+fn main() -> ExitCode {
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if args.is_empty() {
+        args.push("levels".to_string());
+    }
+
+    let mut paths: Vec<PathBuf> = Vec::new();
+    for arg in &args {
+        let path = PathBuf::from(arg);
+        if path.is_dir() {
+            match level_files(&path) {
+                Ok(files) => paths.extend(files),
+                Err(e) => {
+                    eprintln!("{}: no se pudo leer la carpeta: {}", path.display(), e);
+                    return ExitCode::FAILURE;
+                }
+            }
+        } else {
+            paths.push(path);
+        }
+    }
+
+    let (mut errors, mut warnings) = (0, 0);
+    for path in &paths {
+        let issues = match check_level_file(path) {
+            Ok(issues) => issues,
+            Err(e) => {
+                eprintln!("{}: error: no se pudo leer: {}", path.display(), e);
+                errors += 1;
+                continue;
+            }
+        };
+        if issues.is_empty() {
+            println!("{}: ok", path.display());
+        }
+        for issue in &issues {
+            println!("{}: {}", path.display(), issue);
+            match issue.severity {
+                Severity::Error => errors += 1,
+                Severity::Warning => warnings += 1,
+            }
+        }
+    }
+
+    println!("{} nivel(es), {} error(es), {} aviso(s)", paths.len(), errors, warnings);
+    if errors > 0 { ExitCode::FAILURE } else { ExitCode::SUCCESS }
+}
@@ -1,80 +1,321 @@
 use raylib::prelude::*;
+use crate::explored::Explored;
+use crate::framebuffer::FrameBuffer;
+use crate::levels::LevelMeta;
 use crate::maze::Maze;
 use crate::player::Player;
+use crate::tile::{Tile, WallTex};
+
+// Atenuación por distancia (paredes, piso y techo usan la misma)
+const SHADE_K: f32 = 0.15;
+const MIN_BRIGHTNESS: f32 = 0.25;
+
+/// Textura en memoria (CPU) para muestrear píxel a píxel
+pub struct TexPixels {
+    w: usize,
+    h: usize,
+    px: Vec<Color>,
+}
+
+impl TexPixels {
+    pub fn load(path: &str) -> Option<Self> {
+        let img = Image::load_image(path).ok()?;
+        let (w, h) = (img.width() as usize, img.height() as usize);
+        if w == 0 || h == 0 {
+            return None;
+        }
+        Some(Self { w, h, px: img.get_image_data().to_vec() })
+    }
+
+    pub fn solid(color: Color) -> Self {
+        Self { w: 1, h: 1, px: vec![color] }
+    }
+
+    pub fn width(&self) -> usize { self.w }
+    pub fn height(&self) -> usize { self.h }
+
+    /// u,v en unidades de textura (se repite fuera de [0,1))
+    fn sample(&self, u: f32, v: f32) -> Color {
+        let x = ((u - u.floor()) * self.w as f32) as usize;
+        let y = ((v - v.floor()) * self.h as f32) as usize;
+        self.px[y.min(self.h - 1) * self.w + x.min(self.w - 1)]
+    }
+}
+
+/// Texturas de piso, techo y cielo del nivel activo
+pub struct PlaneTextures {
+    pub floor: TexPixels,
+    pub ceiling: TexPixels,
+    pub sky: TexPixels,
+}
+
+impl PlaneTextures {
+    /// Si falta un archivo se usa un color plano (los colores de antes)
+    pub fn load(floor: &str, ceiling: &str, sky: Option<&str>) -> Self {
+        Self {
+            floor: TexPixels::load(floor).unwrap_or_else(|| TexPixels::solid(Color::new(25, 20, 15, 255))),
+            ceiling: TexPixels::load(ceiling).unwrap_or_else(|| TexPixels::solid(Color::new(15, 15, 25, 255))),
+            sky: sky
+                .and_then(TexPixels::load)
+                .unwrap_or_else(|| TexPixels::solid(Color::new(20, 26, 48, 255))),
+        }
+    }
+}
+
+/// Texturas que usa la escena 3D del nivel activo, más el color de niebla
+pub struct SceneTextures<'a> {
+    pub wall: &'a TexPixels,
+    pub door: &'a TexPixels,
+    pub planes: &'a PlaneTextures,
+    pub fog: Color,
+}
+
+/// Todas las texturas de un nivel, cargadas según su `LevelMeta`
+pub struct LevelTextures {
+    pub wall: TexPixels,
+    pub door: TexPixels,
+    pub planes: PlaneTextures,
+    pub fog: Color,
+}
+
+impl LevelTextures {
+    /// Si el nivel pide una pared/puerta que no existe se usan las de siempre
+    pub fn load(meta: &LevelMeta) -> Result<Self, String> {
+        let wall = TexPixels::load(&meta.wall)
+            .or_else(|| TexPixels::load("assets/wall.png"))
+            .ok_or("Falta assets/wall.png")?;
+        let door = TexPixels::load(&meta.door)
+            .or_else(|| TexPixels::load("assets/door.png"))
+            .ok_or("Falta assets/door.png")?;
+        let (r, g, b) = meta.fog;
+        Ok(Self {
+            wall,
+            door,
+            planes: PlaneTextures::load(&meta.floor, &meta.ceiling, meta.sky.as_deref()),
+            fog: Color::new(r, g, b, 255),
+        })
+    }
+
+    pub fn scene(&self) -> SceneTextures<'_> {
+        SceneTextures { wall: &self.wall, door: &self.door, planes: &self.planes, fog: self.fog }
+    }
+}
+
+/// Objeto del mundo dibujado como billboard (siempre de frente a la cámara)
+pub struct Sprite<'a> {
+    pub pos: Vector2,     // posición en el mundo (píxeles)
+    pub tex: &'a TexPixels,
+    pub scale: f32,       // alto relativo a una celda (1.0 = pared completa)
+}
 
 struct Hit {
     perp_px: f32,       
     perp_cells: f32,    
     tex_x: f32,
-    tile: char,
+    tile: Tile,
     side: i32,
     dir: Vector2,
 }
 
+/// Campo de visión horizontal por defecto (60°); el juego usa el de las preferencias.
+/// El minimapa no tiene uno propio: dibuja los rayos que tiró `render_3d_scene`.
+pub const DEFAULT_FOV: f32 = std::f32::consts::FRAC_PI_3;
+
+// alcance de un rayo que no toca ninguna pared (cielo abierto), en celdas
+const MAX_RAY_CELLS: f32 = 64.0;
+
+/// Dibuja la vista 3D completa (piso, techo, paredes y sprites) en el framebuffer.
+/// `fov` = campo de visión horizontal en radianes. Si se pasa `explored`, cada
+/// rayo marca ahí las celdas que cruza y la pared donde pega, y deja su extremo.
 pub fn render_3d_scene(
-    d: &mut RaylibDrawHandle,
+    fb: &mut FrameBuffer,
     maze: &Maze,
     player: &Player,
-    wall_tex: &Texture2D,
-    door_tex: &Texture2D,
+    tex: &SceneTextures,
+    sprites: &[Sprite],
+    fov: f32,
+    mut explored: Option<&mut Explored>,
 ) {
-    let (sw, sh) = (d.get_screen_width(), d.get_screen_height());
+    let (sw, sh) = (fb.width(), fb.height());
     let (swf, shf) = (sw as f32, sh as f32);
 
-    d.draw_rectangle(0, 0, sw, sh / 2, Color::new(15, 15, 25, 255));
-    d.draw_rectangle(0, sh / 2, sw, sh / 2, Color::new(25, 20, 15, 255));
+    fb.clear(Color::BLACK);
+
+    // piso y techo por píxel
+    cast_planes(fb, maze, player, tex.planes, tex.fog, fov);
+
+    // z-buffer: distancia (px) de la pared en cada columna, para recortar sprites
+    let mut zbuf = vec![f32::INFINITY; sw as usize];
+
+    if let Some(e) = explored.as_deref_mut() {
+        e.begin_frame(player.pos);
+    }
 
-    let fov = std::f32::consts::FRAC_PI_3; // 60°
     for x in 0..sw {
         let cam_x = (2.0 * x as f32 / swf) - 1.0; // -1..+1
         let ray_angle = player.a + cam_x * (fov * 0.5);
 
-        if let Some(mut hit) = cast_ray(maze, player.pos, ray_angle) {
+        let hit = cast_ray(maze, player.pos, ray_angle, explored.as_deref_mut());
+        if let Some(e) = explored.as_deref_mut() {
+            // perp_px es el largo a lo largo del rayo (la dirección es unitaria)
+            let len = hit.as_ref().map_or(MAX_RAY_CELLS * maze.block_size() as f32, |h| h.perp_px);
+            e.add_ray(player.pos + Vector2::new(ray_angle.cos(), ray_angle.sin()) * len);
+        }
+        if let Some(mut hit) = hit {
+            zbuf[x as usize] = hit.perp_px;
+
             let bs = maze.block_size() as f32;
-            let mut column_h = (shf * bs) / hit.perp_px.max(0.0001);
-            column_h = column_h.min(shf * 4.0); // clamp para muy cerca
+            let column_h = (shf * bs) / hit.perp_px.max(0.0001);
 
-            let top = ((shf - column_h) * 0.5).max(0.0);
-            let bottom = ((shf + column_h) * 0.5).min(shf);
+            // la textura se mapea a la columna sin recortar; solo se pintan las filas visibles
+            let top_f = (shf - column_h) * 0.5;
+            let top = top_f.max(0.0) as i32;
+            let bottom = ((shf + column_h) * 0.5).min(shf) as i32;
 
-            let tex = if hit.tile == 'D' { door_tex } else { wall_tex };
-            let (tw, th) = (tex.width() as f32, tex.height() as f32);
+            let wall = match hit.tile.def().texture {
+                Some(WallTex::Door) => tex.door,
+                _ => tex.wall,
+            };
 
             if (hit.side == 0 && hit.dir.x > 0.0) || (hit.side == 1 && hit.dir.y < 0.0) {
                 hit.tex_x = 1.0 - hit.tex_x;
             }
 
-            let src = Rectangle {
-                x: (hit.tex_x * (tw - 1.0)).clamp(0.0, tw - 1.0),
-                y: 0.0,
-                width: 1.0,
-                height: th,
-            };
-            let dst = Rectangle {
-                x: x as f32,
-                y: top,
-                width: 1.0,
-                height: bottom - top,
-            };
+            let shade = shade_for(hit.perp_cells);
+            for y in top..bottom {
+                let v = (y as f32 + 0.5 - top_f) / column_h;
+                fb.set_pixel(x, y, shaded(wall.sample(hit.tex_x, v), shade, tex.fog));
+            }
+        }
+    }
+
+    draw_sprites(fb, maze, player, sprites, &zbuf, fov, tex.fog);
+}
+
+// Billboards ordenados de lejos a cerca; cada columna se recorta contra el z-buffer
+fn draw_sprites(
+    fb: &mut FrameBuffer,
+    maze: &Maze,
+    player: &Player,
+    sprites: &[Sprite],
+    zbuf: &[f32],
+    fov: f32,
+    fog: Color,
+) {
+    let (swf, shf) = (fb.width() as f32, fb.height() as f32);
+    let bs = maze.block_size() as f32;
+
+    let mut order: Vec<(f32, &Sprite)> = sprites
+        .iter()
+        .map(|s| ((s.pos - player.pos).length(), s))
+        .collect();
+    order.sort_by(|a, b| b.0.total_cmp(&a.0));
+
+    for (dist, s) in order {
+        if dist < 1.0 {
+            continue;
+        }
+        // ángulo relativo a la vista, en [-PI, PI]
+        let rel = s.pos - player.pos;
+        let mut ang = rel.y.atan2(rel.x) - player.a;
+        while ang > std::f32::consts::PI { ang -= std::f32::consts::TAU; }
+        while ang < -std::f32::consts::PI { ang += std::f32::consts::TAU; }
+
+        // misma proyección que los rayos: columna lineal en el ángulo
+        let screen_x = (ang / (fov * 0.5) + 1.0) * 0.5 * swf;
+        let full_h = (shf * bs) / dist; // alto de una pared a esta distancia
+        let h = full_h * s.scale;
+        let w = h * s.tex.width() as f32 / s.tex.height() as f32;
 
-            let k = 0.15;                 
-            let min_brightness = 0.25;     
-            let shade_base = 1.0 / (1.0 + k * hit.perp_cells);
-            let shade = min_brightness + (1.0 - min_brightness) * shade_base;
+        let left = screen_x - w * 0.5;
+        if left + w < 0.0 || left >= swf {
+            continue;
+        }
+        let bottom = (shf + full_h) * 0.5; // apoyado en el piso
+        let top = bottom - h;
 
-            let tint = Color::new(
-                (255.0 * shade) as u8,
-                (255.0 * shade) as u8,
-                (255.0 * shade) as u8,
-                255,
-            );
+        let shade = shade_for(dist / bs);
+        let (x0, x1) = (left.max(0.0) as i32, (left + w).min(swf) as i32);
+        let (y0, y1) = (top.max(0.0) as i32, bottom.min(shf) as i32);
 
-            d.draw_texture_pro(tex, src, dst, Vector2::zero(), 0.0, tint);
+        for x in x0..x1 {
+            if dist >= zbuf[x as usize] {
+                continue;
+            }
+            let u = (x as f32 + 0.5 - left) / w;
+            for y in y0..y1 {
+                let c = s.tex.sample(u, (y as f32 + 0.5 - top) / h);
+                if c.a < 128 {
+                    continue; // transparente
+                }
+                fb.set_pixel(x, y, shaded(c, shade, fog));
+            }
         }
     }
 }
 
-fn cast_ray(maze: &Maze, origin_px: Vector2, angle: f32) -> Option<Hit> {
+fn shade_for(dist_cells: f32) -> f32 {
+    let shade_base = 1.0 / (1.0 + SHADE_K * dist_cells);
+    MIN_BRIGHTNESS + (1.0 - MIN_BRIGHTNESS) * shade_base
+}
+
+// Mezcla hacia el color de niebla: shade 1 = color original, 0 = niebla pura
+fn shaded(c: Color, shade: f32, fog: Color) -> Color {
+    let mix = |a: u8, f: u8| (f as f32 + (a as f32 - f as f32) * shade) as u8;
+    Color::new(mix(c.r, fog.r), mix(c.g, fog.g), mix(c.b, fog.b), 255)
+}
+
+// Floor/ceiling casting: cada fila bajo el horizonte está a una distancia fija,
+// la misma que usaría una pared cuya base cae en esa fila.
+fn cast_planes(
+    fb: &mut FrameBuffer,
+    maze: &Maze,
+    player: &Player,
+    planes: &PlaneTextures,
+    fog: Color,
+    fov: f32,
+) {
+    let (w, h) = (fb.width(), fb.height());
+    let half = h as f32 * 0.5;
+    let bs = maze.block_size() as f32;
+    let (pos_x, pos_y) = (player.pos.x / bs, player.pos.y / bs);
+
+    // ángulo y dirección de cada columna (igual que los rayos de las paredes)
+    let cols: Vec<(f32, f32, f32)> = (0..w)
+        .map(|x| {
+            let cam_x = (2.0 * x as f32 / w as f32) - 1.0;
+            let a = player.a + cam_x * (fov * 0.5);
+            (a, a.cos(), a.sin())
+        })
+        .collect();
+
+    for y in (h / 2)..h {
+        let p = y as f32 - half + 0.5;
+        let dist = half / p; // en celdas
+        let shade = shade_for(dist);
+        let ceil_y = h - 1 - y;
+
+        for x in 0..w {
+            let (a, dir_x, dir_y) = cols[x as usize];
+            let wx = pos_x + dir_x * dist;
+            let wy = pos_y + dir_y * dist;
+
+            // una repetición de textura por celda
+            fb.set_pixel(x, y, shaded(planes.floor.sample(wx, wy), shade, fog));
+
+            let ceil = if maze.has_ceiling(wx.floor() as isize, wy.floor() as isize) {
+                shaded(planes.ceiling.sample(wx, wy), shade, fog)
+            } else {
+                // cielo abierto: panorama según el ángulo, sin atenuación
+                let u = a / std::f32::consts::TAU;
+                planes.sky.sample(u, ceil_y as f32 / half)
+            };
+            fb.set_pixel(x, ceil_y, ceil);
+        }
+    }
+}
+
+fn cast_ray(maze: &Maze, origin_px: Vector2, angle: f32, mut explored: Option<&mut Explored>) -> Option<Hit> {
     let bs = maze.block_size() as f32;
 
     // Posición/dirección en unidades de CELDA
@@ -103,9 +344,16 @@ fn cast_ray(maze: &Maze, origin_px: Vector2, angle: f32) -> Option<Hit> {
         ( 1, ((map_y as f32 + 1.0) - pos_y) * delta_y)
     };
 
+    let mut mark = |x: i32, y: i32| {
+        if let Some(e) = explored.as_deref_mut() {
+            e.mark(x, y);
+        }
+    };
+    mark(map_x, map_y);
+
     // DDA
     let mut side = 0; // 0 = vertical (eje X), 1 = horizontal (eje Y)
-    let mut tile = ' ';
+    let mut tile = Tile::Empty;
     for _ in 0..4096 {
         if side_x < side_y {
             side_x += delta_x;
@@ -117,12 +365,13 @@ fn cast_ray(maze: &Maze, origin_px: Vector2, angle: f32) -> Option<Hit> {
             side = 1;
         }
         tile = maze.cell(map_x as isize, map_y as isize);
-        if tile == '#' || tile == 'D' {
+        mark(map_x, map_y);
+        if tile.is_solid() {
             break;
         }
     }
 
-    if tile != '#' && tile != 'D' {
+    if !tile.is_solid() {
         return None;
     }
 
@@ -0,0 +1,93 @@
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+// Archivos de configuración del usuario: texto `clave = valor`, uno por línea,
+// con comentarios `#`. Cada archivo (controles, preferencias) decide qué claves
+// entiende; las que no reconoce se avisan por stderr y se ignoran.
+
+const APP_DIR: &str = "escape-reputation";
+
+/// Carpeta de configuración: `$XDG_CONFIG_HOME`, `~/.config` o `%APPDATA%`,
+/// más una subcarpeta del juego. None si no hay ninguna de esas variables.
+pub fn config_dir() -> Option<PathBuf> {
+    let env_dir = |var: &str| std::env::var_os(var).filter(|v| !v.is_empty()).map(PathBuf::from);
+    let base = env_dir("XDG_CONFIG_HOME")
+        .or_else(|| env_dir("HOME").map(|h| h.join(".config")))
+        .or_else(|| env_dir("APPDATA"))?;
+    Some(base.join(APP_DIR))
+}
+
+/// Ruta de un archivo dentro de `config_dir`
+pub fn config_path(file: &str) -> Option<PathBuf> {
+    config_dir().map(|d| d.join(file))
+}
+
+/// Lee `file` de `config_dir` y lo arma con `from_kv`; sin carpeta o si no se
+/// puede leer, los valores por defecto
+pub fn load<T: Default>(file: &str, from_kv: impl FnOnce(&[(String, String)], &str) -> T) -> T {
+    let Some(path) = config_path(file) else { return T::default(); };
+    match read_kv(&path) {
+        Ok(entries) => from_kv(&entries, &path.display().to_string()),
+        Err(e) => {
+            eprintln!("{}: {}", path.display(), e);
+            T::default()
+        }
+    }
+}
+
+/// `write_kv` sobre `file` dentro de `config_dir`
+pub fn save(file: &str, header: &str, entries: &[(String, String)]) -> io::Result<()> {
+    let path = config_path(file).ok_or_else(|| io::Error::other("no hay carpeta de configuración (HOME/APPDATA)"))?;
+    write_kv(&path, header, entries)
+}
+
+/// Pares `clave = valor` en orden. Las líneas vacías y los comentarios se saltan;
+/// las que no tienen `=` se avisan por stderr.
+pub fn parse_kv(text: &str, origin: &str) -> Vec<(String, String)> {
+    let mut out = Vec::new();
+    for (n, raw) in text.lines().enumerate() {
+        let line = raw.split('#').next().unwrap_or("").trim();
+        if line.is_empty() {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((k, v)) => out.push((k.trim().to_string(), v.trim().to_string())),
+            None => eprintln!("{}:{}: se esperaba 'clave = valor'", origin, n + 1),
+        }
+    }
+    out
+}
+
+/// `parse_kv` de un archivo; si no existe retorna una lista vacía (se usan los valores por defecto)
+pub fn read_kv(path: &Path) -> io::Result<Vec<(String, String)>> {
+    match fs::read_to_string(path) {
+        Ok(text) => Ok(parse_kv(&text, &path.display().to_string())),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Escribe los pares con un comentario de cabecera, creando la carpeta si hace falta
+pub fn write_kv(path: &Path, header: &str, entries: &[(String, String)]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    let mut text = String::new();
+    for line in header.lines() {
+        text.push_str(&format!("# {}\n", line));
+    }
+    for (k, v) in entries {
+        text.push_str(&format!("{} = {}\n", k, v));
+    }
+    fs::write(path, text)
+}
+
+/// `true`/`false` (también `1`/`0`, `si`/`no`)
+pub fn parse_bool(v: &str) -> Option<bool> {
+    match v.to_ascii_lowercase().as_str() {
+        "true" | "1" | "si" | "sí" | "on" => Some(true),
+        "false" | "0" | "no" | "off" => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,327 @@
+use raylib::prelude::*;
+use raylib::consts::GamepadButton;
+
+use crate::config;
+
+// Mapa de acciones: qué tecla y qué botón del mando dispara cada acción.
+// `InputSnapshot::read` y las pantallas de main.rs preguntan por acciones,
+// nunca por teclas. Se guarda en `controls.cfg` dentro de `config::config_dir`:
+//
+//   move_forward = W / -
+//   use = E / RIGHT_FACE_DOWN
+//   mouse_sensitivity = 0.0032
+//
+// La navegación de menús (flechas, ENTER, dígitos, ESC) queda fija a propósito:
+// así un mal mapeo nunca deja al jugador sin poder volver a cambiarlo.
+
+const FILE: &str = "controls.cfg";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum InputAction {
+    MoveForward,
+    MoveBack,
+    StrafeLeft,
+    StrafeRight,
+    TurnLeft,
+    TurnRight,
+    Sprint,
+    Use,
+    Menu,
+    Restart,
+    Replay,
+    Pause,
+    Map,
+    MapZoomIn,
+    MapZoomOut,
+    Hint,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 16] = [
+        InputAction::MoveForward,
+        InputAction::MoveBack,
+        InputAction::StrafeLeft,
+        InputAction::StrafeRight,
+        InputAction::TurnLeft,
+        InputAction::TurnRight,
+        InputAction::Sprint,
+        InputAction::Use,
+        InputAction::Menu,
+        InputAction::Restart,
+        InputAction::Replay,
+        InputAction::Pause,
+        InputAction::Map,
+        InputAction::MapZoomIn,
+        InputAction::MapZoomOut,
+        InputAction::Hint,
+    ];
+
+    /// Clave en `controls.cfg`
+    pub fn name(self) -> &'static str {
+        match self {
+            InputAction::MoveForward => "move_forward",
+            InputAction::MoveBack => "move_back",
+            InputAction::StrafeLeft => "strafe_left",
+            InputAction::StrafeRight => "strafe_right",
+            InputAction::TurnLeft => "turn_left",
+            InputAction::TurnRight => "turn_right",
+            InputAction::Sprint => "sprint",
+            InputAction::Use => "use",
+            InputAction::Menu => "menu",
+            InputAction::Restart => "restart",
+            InputAction::Replay => "replay",
+            InputAction::Pause => "pause",
+            InputAction::Map => "map",
+            InputAction::MapZoomIn => "map_zoom_in",
+            InputAction::MapZoomOut => "map_zoom_out",
+            InputAction::Hint => "hint",
+        }
+    }
+
+    /// Texto para la pantalla de controles
+    pub fn label(self) -> &'static str {
+        match self {
+            InputAction::MoveForward => "Avanzar",
+            InputAction::MoveBack => "Retroceder",
+            InputAction::StrafeLeft => "Paso a la izquierda",
+            InputAction::StrafeRight => "Paso a la derecha",
+            InputAction::TurnLeft => "Girar a la izquierda",
+            InputAction::TurnRight => "Girar a la derecha",
+            InputAction::Sprint => "Correr",
+            InputAction::Use => "Usar (puerta)",
+            InputAction::Menu => "Volver al menú",
+            InputAction::Restart => "Reintentar nivel",
+            InputAction::Replay => "Ver repetición",
+            InputAction::Pause => "Pausa",
+            InputAction::Map => "Mapa completo",
+            InputAction::MapZoomIn => "Acercar el minimapa",
+            InputAction::MapZoomOut => "Alejar el minimapa",
+            InputAction::Hint => "Pista (con ayudas)",
+        }
+    }
+
+    fn index(self) -> usize {
+        InputAction::ALL.iter().position(|&a| a == self).unwrap_or(0)
+    }
+}
+
+/// Tecla y botón de una acción (cualquiera de los dos la dispara)
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub struct Binding {
+    pub key: Option<KeyboardKey>,
+    pub button: Option<GamepadButton>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Controls {
+    bindings: [Binding; InputAction::ALL.len()],
+    pub mouse_sensitivity: f32, // radianes por píxel de mouse
+    pub deadzone: f32,          // sticks analógicos
+    pub invert_turn: bool,      // mouse y stick derecho
+    pub invert_move: bool,      // eje vertical del stick izquierdo
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        use GamepadButton::*;
+        use KeyboardKey::*;
+        let mut c = Self {
+            bindings: [Binding::default(); InputAction::ALL.len()],
+            mouse_sensitivity: 0.0032,
+            deadzone: 0.18,
+            invert_turn: false,
+            invert_move: false,
+        };
+        let defaults = [
+            (InputAction::MoveForward, Some(KEY_W), None),
+            (InputAction::MoveBack, Some(KEY_S), None),
+            (InputAction::StrafeLeft, Some(KEY_A), None),
+            (InputAction::StrafeRight, Some(KEY_D), None),
+            (InputAction::TurnLeft, Some(KEY_LEFT), Some(GAMEPAD_BUTTON_LEFT_FACE_LEFT)),
+            (InputAction::TurnRight, Some(KEY_RIGHT), Some(GAMEPAD_BUTTON_LEFT_FACE_RIGHT)),
+            (InputAction::Sprint, Some(KEY_LEFT_SHIFT), Some(GAMEPAD_BUTTON_LEFT_TRIGGER_1)),
+            (InputAction::Use, Some(KEY_E), Some(GAMEPAD_BUTTON_RIGHT_FACE_DOWN)),
+            (InputAction::Menu, Some(KEY_M), Some(GAMEPAD_BUTTON_RIGHT_FACE_RIGHT)),
+            (InputAction::Restart, Some(KEY_R), Some(GAMEPAD_BUTTON_RIGHT_FACE_UP)),
+            (InputAction::Replay, Some(KEY_V), Some(GAMEPAD_BUTTON_RIGHT_FACE_LEFT)),
+            (InputAction::Pause, Some(KEY_P), Some(GAMEPAD_BUTTON_MIDDLE_RIGHT)), // Start
+            (InputAction::Map, Some(KEY_TAB), Some(GAMEPAD_BUTTON_MIDDLE_LEFT)),  // Select
+            (InputAction::MapZoomIn, Some(KEY_EQUAL), Some(GAMEPAD_BUTTON_RIGHT_TRIGGER_1)),
+            (InputAction::MapZoomOut, Some(KEY_MINUS), Some(GAMEPAD_BUTTON_RIGHT_TRIGGER_2)),
+            (InputAction::Hint, Some(KEY_H), Some(GAMEPAD_BUTTON_LEFT_THUMB)),
+        ];
+        for (action, key, button) in defaults {
+            c.bindings[action.index()] = Binding { key, button };
+        }
+        c
+    }
+}
+
+impl Controls {
+    pub fn binding(&self, action: InputAction) -> Binding {
+        self.bindings[action.index()]
+    }
+
+    pub fn set_binding(&mut self, action: InputAction, binding: Binding) {
+        self.bindings[action.index()] = binding;
+    }
+
+    /// Tecla o botón mantenido
+    pub fn down(&self, rl: &RaylibHandle, action: InputAction) -> bool {
+        let b = self.binding(action);
+        b.key.is_some_and(|k| rl.is_key_down(k))
+            || b.button.is_some_and(|btn| rl.is_gamepad_button_down(0, btn))
+    }
+
+    /// Tecla o botón recién apretado (flanco)
+    pub fn pressed(&self, rl: &RaylibHandle, action: InputAction) -> bool {
+        let b = self.binding(action);
+        b.key.is_some_and(|k| rl.is_key_pressed(k))
+            || b.button.is_some_and(|btn| rl.is_gamepad_button_pressed(0, btn))
+    }
+
+    /// Lee `controls.cfg`; lo que falte o no se entienda queda por defecto
+    pub fn load() -> Self {
+        config::load(FILE, Self::from_kv)
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        config::save(FILE, "controles: tecla / botón del mando (\"-\" = sin asignar)", &self.to_kv())
+    }
+
+    pub fn from_kv(entries: &[(String, String)], origin: &str) -> Self {
+        let mut c = Self::default();
+        for (k, v) in entries {
+            let ok = match k.as_str() {
+                "mouse_sensitivity" => v.parse().map(|x| c.mouse_sensitivity = x).is_ok(),
+                "deadzone" => v.parse::<f32>().map(|x| c.deadzone = x.clamp(0.0, 0.9)).is_ok(),
+                "invert_turn" => config::parse_bool(v).map(|x| c.invert_turn = x).is_some(),
+                "invert_move" => config::parse_bool(v).map(|x| c.invert_move = x).is_some(),
+                name => match InputAction::ALL.iter().find(|a| a.name() == name) {
+                    Some(&action) => parse_binding(v).map(|b| c.set_binding(action, b)).is_some(),
+                    None => {
+                        eprintln!("{}: clave desconocida '{}'", origin, k);
+                        continue;
+                    }
+                },
+            };
+            if !ok {
+                eprintln!("{}: valor inválido para {}: '{}'", origin, k, v);
+            }
+        }
+        c
+    }
+
+    pub fn to_kv(&self) -> Vec<(String, String)> {
+        let mut out: Vec<(String, String)> = InputAction::ALL
+            .iter()
+            .map(|&a| (a.name().to_string(), binding_text(self.binding(a))))
+            .collect();
+        out.push(("mouse_sensitivity".into(), self.mouse_sensitivity.to_string()));
+        out.push(("deadzone".into(), self.deadzone.to_string()));
+        out.push(("invert_turn".into(), self.invert_turn.to_string()));
+        out.push(("invert_move".into(), self.invert_move.to_string()));
+        out
+    }
+}
+
+/// "W / RIGHT_FACE_DOWN"; `-` donde no hay nada asignado
+pub fn binding_text(b: Binding) -> String {
+    format!(
+        "{} / {}",
+        b.key.map(key_name).unwrap_or_else(|| "-".into()),
+        b.button.map(button_name).unwrap_or_else(|| "-".into()),
+    )
+}
+
+fn parse_binding(v: &str) -> Option<Binding> {
+    let (key, button) = v.split_once('/').unwrap_or((v, "-"));
+    let (key, button) = (key.trim(), button.trim());
+    Some(Binding {
+        key: if key == "-" { None } else { Some(parse_key(key)?) },
+        button: if button == "-" { None } else { Some(parse_button(button)?) },
+    })
+}
+
+/// Nombre de raylib sin el prefijo: `KEY_LEFT_SHIFT` → `LEFT_SHIFT`
+pub fn key_name(k: KeyboardKey) -> String {
+    let full = format!("{:?}", k);
+    full.strip_prefix("KEY_").unwrap_or(&full).to_string()
+}
+
+pub fn parse_key(name: &str) -> Option<KeyboardKey> {
+    let name = name.to_ascii_uppercase();
+    (1..400).filter_map(key_from_i32).find(|&k| key_name(k) == name)
+}
+
+const BUTTONS: [GamepadButton; 17] = {
+    use GamepadButton::*;
+    [
+        GAMEPAD_BUTTON_LEFT_FACE_UP, GAMEPAD_BUTTON_LEFT_FACE_RIGHT,
+        GAMEPAD_BUTTON_LEFT_FACE_DOWN, GAMEPAD_BUTTON_LEFT_FACE_LEFT,
+        GAMEPAD_BUTTON_RIGHT_FACE_UP, GAMEPAD_BUTTON_RIGHT_FACE_RIGHT,
+        GAMEPAD_BUTTON_RIGHT_FACE_DOWN, GAMEPAD_BUTTON_RIGHT_FACE_LEFT,
+        GAMEPAD_BUTTON_LEFT_TRIGGER_1, GAMEPAD_BUTTON_LEFT_TRIGGER_2,
+        GAMEPAD_BUTTON_RIGHT_TRIGGER_1, GAMEPAD_BUTTON_RIGHT_TRIGGER_2,
+        GAMEPAD_BUTTON_MIDDLE_LEFT, GAMEPAD_BUTTON_MIDDLE, GAMEPAD_BUTTON_MIDDLE_RIGHT,
+        GAMEPAD_BUTTON_LEFT_THUMB, GAMEPAD_BUTTON_RIGHT_THUMB,
+    ]
+};
+
+/// `GAMEPAD_BUTTON_RIGHT_FACE_DOWN` → `RIGHT_FACE_DOWN`
+pub fn button_name(b: GamepadButton) -> String {
+    let full = format!("{:?}", b);
+    full.strip_prefix("GAMEPAD_BUTTON_").unwrap_or(&full).to_string()
+}
+
+pub fn parse_button(name: &str) -> Option<GamepadButton> {
+    let name = name.to_ascii_uppercase();
+    BUTTONS.into_iter().find(|&b| button_name(b) == name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kv(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn kv_round_trip() {
+        let mut c = Controls::default();
+        c.set_binding(InputAction::Use, Binding { key: Some(KeyboardKey::KEY_F), button: None });
+        c.set_binding(InputAction::Map, Binding { key: None, button: Some(GamepadButton::GAMEPAD_BUTTON_RIGHT_THUMB) });
+        c.set_binding(InputAction::Hint, Binding::default());
+        c.mouse_sensitivity = 0.005;
+        c.invert_move = true;
+        assert_eq!(Controls::from_kv(&c.to_kv(), "test"), c);
+    }
+
+    #[test]
+    fn bindings_by_name() {
+        let c = Controls::from_kv(
+            &kv(&[
+                ("use", "left_control / right_face_up"), // sin importar mayúsculas
+                ("map", "- / MIDDLE"),
+                ("sprint", "Q"), // sin botón
+            ]),
+            "test",
+        );
+        let b = c.binding(InputAction::Use);
+        assert_eq!((b.key, b.button), (Some(KeyboardKey::KEY_LEFT_CONTROL), Some(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_UP)));
+        assert_eq!(c.binding(InputAction::Map), Binding { key: None, button: Some(GamepadButton::GAMEPAD_BUTTON_MIDDLE) });
+        assert_eq!(c.binding(InputAction::Sprint), Binding { key: Some(KeyboardKey::KEY_Q), button: None });
+    }
+
+    #[test]
+    fn unknown_names_keep_the_default() {
+        let c = Controls::from_kv(
+            &kv(&[("use", "NOPE / -"), ("map", "TAB / BOTON_RARO"), ("volar", "F"), ("deadzone", "x")]),
+            "test",
+        );
+        assert_eq!(c, Controls::default());
+        assert_eq!(parse_binding("TAB / -"), Some(Binding { key: Some(KeyboardKey::KEY_TAB), button: None }));
+        assert_eq!(parse_binding("TAB / BOTON_RARO"), None);
+    }
+}
@@ -0,0 +1,321 @@
+use raylib::prelude::*;
+
+use crate::caster::{Sprite, TexPixels};
+use crate::levels::LevelMeta;
+use crate::maze::Maze;
+use crate::minimap::{Marker, MarkerKind, Reveal};
+use crate::screamer::{has_los, Screamer, ScreamerEvent, HEAR_RADIUS};
+
+// Capa de entidades: todo lo dinámico del nivel es una `Entity` con
+// componentes opcionales. Los mapas las crean desde sus tiles (`TileDef::entity`),
+// así que un mapa puede tener cualquier cantidad de 'S' o 'D'.
+// Para agregar un tipo nuevo: variante en `EntityKind`, su armado en
+// `Entity::spawn` y, si corresponde, la fila del tile en `tile.rs`.
+
+// distancia entre pasos de las entidades que caminan solas (zancada más larga que la del jugador)
+const AI_STEP_PIXELS: f32 = 44.0;
+
+// cuerpo del screamer (celdas); el alcance con que atrapa es aparte (@screamer_range)
+const SCREAMER_RADIUS: f32 = 0.3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EntityKind {
+    Screamer,
+    Exit,
+    Start, // donde arrancó el jugador; solo se marca en el mapa
+    Item,  // objeto tirado en el piso: se ve y se marca, no hace nada
+}
+
+/// Texturas de sprites, en CPU (se muestrean en el framebuffer)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SpriteTex {
+    Screamer,
+    Crumb,
+    Item,
+}
+
+pub struct SpriteSheet {
+    pub screamer: TexPixels,
+    pub crumb: TexPixels, // migas de las ayudas (ver `assist::Trail`)
+    pub item: TexPixels,
+}
+
+impl SpriteSheet {
+    pub fn load() -> Result<Self, String> {
+        let screamer = TexPixels::load("assets/screamer.png").ok_or("Falta assets/screamer.png")?;
+        let crumb = TexPixels::solid(Color::new(235, 225, 190, 255));
+        let item = TexPixels::solid(Color::new(120, 200, 80, 255));
+        Ok(Self { screamer, crumb, item })
+    }
+
+    pub fn get(&self, tex: SpriteTex) -> &TexPixels {
+        match tex {
+            SpriteTex::Screamer => &self.screamer,
+            SpriteTex::Crumb => &self.crumb,
+            SpriteTex::Item => &self.item,
+        }
+    }
+}
+
+pub struct SpriteComp {
+    pub tex: SpriteTex,
+    pub scale: f32,
+}
+
+/// Cuerpo circular (radio en celdas): separa las entidades entre sí
+pub struct Collider {
+    pub radius: f32,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Activation {
+    Touch(f32), // el jugador está a esta distancia o menos (celdas, con línea de vista)
+    Use,        // el jugador "usa" (E) mirando la celda de la entidad
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Action {
+    Win,
+    Catch,
+}
+
+pub struct Trigger {
+    pub on: Activation,
+    pub action: Action,
+}
+
+pub struct Entity {
+    pub kind: EntityKind,
+    pub pos: Vector2,
+    pub prev_pos: Vector2, // posición al empezar el último tick (para interpolar el dibujo)
+    pub sprite: Option<SpriteComp>,
+    pub collider: Option<Collider>,
+    pub ai: Option<Screamer>,
+    pub trigger: Option<Trigger>,
+    step_accum: f32, // píxeles caminados desde el último paso (solo las que tienen `ai`)
+}
+
+impl Entity {
+    /// Arma la entidad con sus componentes; None si el nivel la desactiva
+    pub fn spawn(kind: EntityKind, maze: &Maze, cell: (isize, isize), meta: &LevelMeta) -> Option<Entity> {
+        let pos = maze.cell_center_world(cell);
+        let bare = Entity { kind, pos, prev_pos: pos, sprite: None, collider: None, ai: None, trigger: None, step_accum: 0.0 };
+        Some(match kind {
+            EntityKind::Screamer if !meta.screamer.enabled => return None,
+            EntityKind::Screamer => Entity {
+                sprite: Some(SpriteComp { tex: SpriteTex::Screamer, scale: 0.8 }),
+                collider: Some(Collider { radius: SCREAMER_RADIUS }),
+                ai: Some(Screamer::new(cell)),
+                trigger: Some(Trigger { on: Activation::Touch(meta.screamer.range), action: Action::Catch }),
+                ..bare
+            },
+            EntityKind::Exit => Entity {
+                trigger: Some(Trigger { on: Activation::Use, action: Action::Win }),
+                ..bare
+            },
+            EntityKind::Start => bare,
+            EntityKind::Item => Entity { sprite: Some(SpriteComp { tex: SpriteTex::Item, scale: 0.25 }), ..bare },
+        })
+    }
+}
+
+/// Qué pasó en un update del mundo (índice de la entidad que lo causó)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WorldEvent {
+    Ai(usize, ScreamerEvent),
+    Step(usize), // una entidad con `ai` dio un paso (para el sonido)
+    Triggered(usize, Action),
+}
+
+#[derive(Default)]
+pub struct World {
+    pub entities: Vec<Entity>,
+}
+
+impl World {
+    /// Una entidad por cada celda cuyo tile declara `entity`
+    pub fn spawn_from_maze(maze: &Maze, meta: &LevelMeta) -> Self {
+        let mut entities = Vec::new();
+        for j in 0..maze.height() as isize {
+            for i in 0..maze.width() as isize {
+                if let Some(kind) = maze.cell(i, j).def().entity {
+                    entities.extend(Entity::spawn(kind, maze, (i, j), meta));
+                }
+            }
+        }
+        Self { entities }
+    }
+
+    /// IA, separación entre cuerpos y triggers.
+    /// `use_point` = punto frente al jugador si apretó "usar" este frame.
+    pub fn update(
+        &mut self,
+        maze: &Maze,
+        player_pos: Vector2,
+        use_point: Option<Vector2>,
+        footstep: bool,
+        dt: f32,
+    ) -> Vec<WorldEvent> {
+        let mut events = Vec::new();
+
+        for e in &mut self.entities {
+            e.prev_pos = e.pos;
+        }
+        for (idx, e) in self.entities.iter_mut().enumerate() {
+            if let Some(ai) = &mut e.ai
+                && let Some(ev) = ai.update(&mut e.pos, maze, player_pos, footstep, dt)
+            {
+                events.push(WorldEvent::Ai(idx, ev));
+            }
+        }
+
+        self.separate(maze);
+
+        for (idx, e) in self.entities.iter_mut().enumerate() {
+            if e.ai.is_none() {
+                continue;
+            }
+            e.step_accum += (e.pos - e.prev_pos).length();
+            if e.step_accum >= AI_STEP_PIXELS {
+                e.step_accum -= AI_STEP_PIXELS;
+                events.push(WorldEvent::Step(idx));
+            }
+        }
+
+        let bs = maze.block_size() as f32;
+        for (idx, e) in self.entities.iter().enumerate() {
+            let Some(trigger) = &e.trigger else { continue; };
+            let fired = match trigger.on {
+                Activation::Touch(radius) => {
+                    (player_pos - e.pos).length() <= radius * bs && has_los(maze, e.pos, player_pos)
+                }
+                Activation::Use => use_point.is_some_and(|p| maze.world_to_cell(p) == maze.world_to_cell(e.pos)),
+            };
+            if fired {
+                events.push(WorldEvent::Triggered(idx, trigger.action));
+            }
+        }
+        events
+    }
+
+    // Empuja a los pares que se superponen (solo si no termina dentro de una pared)
+    fn separate(&mut self, maze: &Maze) {
+        let bs = maze.block_size() as f32;
+        for a in 0..self.entities.len() {
+            for b in a + 1..self.entities.len() {
+                let (Some(ca), Some(cb)) = (&self.entities[a].collider, &self.entities[b].collider) else { continue; };
+                let min = (ca.radius + cb.radius) * bs;
+                let delta = self.entities[b].pos - self.entities[a].pos;
+                let len = delta.length();
+                if len >= min || len == 0.0 {
+                    continue;
+                }
+                let push = delta * ((min - len) * 0.5 / len);
+                for (idx, dir) in [(a, -1.0), (b, 1.0)] {
+                    let moved = self.entities[idx].pos + push * dir;
+                    if maze.is_walkable(maze.world_to_cell(moved)) {
+                        self.entities[idx].pos = moved;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Sprites de todas las entidades visibles, para `render_3d_scene`.
+    /// `alpha` (0..1) interpola entre el tick anterior y el actual.
+    pub fn sprites<'a>(&self, sheet: &'a SpriteSheet, alpha: f32) -> Vec<Sprite<'a>> {
+        self.entities
+            .iter()
+            .filter_map(|e| {
+                let s = e.sprite.as_ref()?;
+                let pos = e.prev_pos + (e.pos - e.prev_pos) * alpha;
+                Some(Sprite { pos, tex: sheet.get(s.tex), scale: s.scale })
+            })
+            .collect()
+    }
+
+    /// Marcadores para el minimapa; `alpha` como en `sprites`
+    pub fn markers(&self, alpha: f32) -> Vec<Marker> {
+        self.entities
+            .iter()
+            .map(|e| {
+                let pos = e.prev_pos + (e.pos - e.prev_pos) * alpha;
+                // al screamer se lo marca a la distancia a la que él oye los pasos
+                let (kind, reveal) = match e.kind {
+                    EntityKind::Screamer => (MarkerKind::Enemy, Reveal::SightOrHearing(HEAR_RADIUS)),
+                    EntityKind::Exit => (MarkerKind::Objective, Reveal::Explored),
+                    EntityKind::Start => (MarkerKind::Waypoint, Reveal::Always),
+                    EntityKind::Item => (MarkerKind::Item, Reveal::Sight),
+                };
+                Marker { kind, pos, reveal }
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn world(rows: &[&str], meta: &LevelMeta) -> (Maze, World) {
+        let maze = Maze::parse(&rows.join("\n"), 64);
+        let world = World::spawn_from_maze(&maze, meta);
+        (maze, world)
+    }
+
+    fn kinds(world: &World, kind: EntityKind) -> Vec<Vector2> {
+        world.entities.iter().filter(|e| e.kind == kind).map(|e| e.pos).collect()
+    }
+
+    #[test]
+    fn every_s_becomes_a_screamer() {
+        let rows = ["########", "#P S  S#", "#S    D#", "########"];
+        let (maze, w) = world(&rows, &LevelMeta::with_name("test"));
+        let expected: Vec<Vector2> = [(3, 1), (6, 1), (1, 2)].map(|c| maze.cell_center_world(c)).into();
+        assert_eq!(kinds(&w, EntityKind::Screamer), expected);
+        assert!(w.entities.iter().filter(|e| e.kind == EntityKind::Screamer).all(|e| e.ai.is_some() && e.collider.is_some()));
+        assert_eq!(kinds(&w, EntityKind::Exit), [maze.cell_center_world((6, 2))]);
+
+        let mut meta = LevelMeta::with_name("test");
+        meta.screamer.enabled = false;
+        let (_, w) = world(&rows, &meta);
+        assert!(kinds(&w, EntityKind::Screamer).is_empty());
+        assert_eq!(kinds(&w, EntityKind::Exit).len(), 1);
+    }
+
+    #[test]
+    fn exit_fires_only_when_used_on_its_cell() {
+        let (maze, mut w) = world(&["#####", "#P D#", "#####"], &LevelMeta::with_name("test"));
+        let exit = w.entities.iter().position(|e| e.kind == EntityKind::Exit).unwrap();
+        let player = maze.cell_center_world((2, 1));
+        let door = maze.cell_center_world((3, 1));
+
+        let on_door = door - Vector2::new(20.0, 0.0); // dentro de la celda de la puerta
+        let events = w.update(&maze, player, Some(on_door), false, 0.01);
+        assert_eq!(events, [WorldEvent::Triggered(exit, Action::Win)]);
+
+        assert!(w.update(&maze, player, Some(player), false, 0.01).is_empty());
+        assert!(w.update(&maze, player, None, false, 0.01).is_empty());
+    }
+
+    #[test]
+    fn touching_a_screamer_catches() {
+        let (maze, mut w) = world(&["#####", "#P S#", "#####"], &LevelMeta::with_name("test"));
+        let idx = w.entities.iter().position(|e| e.kind == EntityKind::Screamer).unwrap();
+        let on_top = w.entities[idx].pos;
+        let events = w.update(&maze, on_top, None, false, 0.01);
+        assert!(events.contains(&WorldEvent::Triggered(idx, Action::Catch)));
+    }
+
+    #[test]
+    fn overlapping_bodies_are_pushed_apart() {
+        let (maze, mut w) = world(&["######", "#PSS #", "######"], &LevelMeta::with_name("test"));
+        let bs = maze.block_size() as f32;
+        let base = maze.cell_center_world((2, 1));
+        w.entities[1].pos = base;
+        w.entities[2].pos = base + Vector2::new(0.1 * bs, 0.0);
+        w.separate(&maze);
+        let gap = (w.entities[2].pos - w.entities[1].pos).length();
+        assert!((gap - 2.0 * SCREAMER_RADIUS * bs).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,215 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+
+use raylib::prelude::Vector2;
+
+use crate::config;
+use crate::levels::LevelSource;
+use crate::maze::Maze;
+
+// Niebla de guerra del minimapa: qué celdas vio el jugador. Las marcan los
+// rayos de `render_3d_scene` (cada celda que cruza un rayo y la pared donde
+// pega). `seen` se acumula y se guarda por nivel en la carpeta de
+// configuración hasta que el jugador lo borra (`reset`, desde la pausa); los
+// laberintos aleatorios no se guardan, cada semilla se juega una vez.
+// `visible` es solo lo del último frame dibujado, igual que los extremos de
+// los rayos (el cono de visión real que dibuja el minimapa).
+//
+// Formato (`explored/<nivel>.map`), '#' = vista:
+//
+//   escape-explored 1
+//   size 14 10
+//   ##....
+//   ...
+
+const MAGIC: &str = "escape-explored 1";
+const DIR: &str = "explored";
+
+#[derive(Clone, Debug, Default)]
+pub struct Explored {
+    level: Option<LevelSource>, // None: no se guarda (sin nivel, o de una repetición)
+    width: usize,
+    height: usize,
+    seen: Vec<bool>,
+    visible: Vec<bool>,
+    eye: Vector2,       // desde dónde salieron los rayos del último frame (píxeles)
+    rays: Vec<Vector2>, // dónde terminó cada rayo, de izquierda a derecha de la pantalla
+}
+
+impl Explored {
+    /// Nada visto, del tamaño del laberinto
+    pub fn new(level: LevelSource, maze: &Maze) -> Self {
+        Self::blank(Some(level), maze)
+    }
+
+    /// Igual que `new` pero nunca se guarda: lo que se va viendo en una repetición
+    pub fn unsaved(maze: &Maze) -> Self {
+        Self::blank(None, maze)
+    }
+
+    fn blank(level: Option<LevelSource>, maze: &Maze) -> Self {
+        let (width, height) = (maze.width(), maze.height());
+        Self {
+            level,
+            width,
+            height,
+            seen: vec![false; width * height],
+            visible: vec![false; width * height],
+            ..Self::default()
+        }
+    }
+
+    /// Lo guardado para este nivel; si no hay (o no corresponde al laberinto) arranca de cero
+    pub fn load(level: &LevelSource, maze: &Maze) -> Self {
+        let mut e = Self::new(level.clone(), maze);
+        let Some(path) = file_for(level).filter(|_| is_persistent(level)) else { return e; };
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                if let Err(msg) = e.parse_seen(&text) {
+                    eprintln!("{}: {} (se ignora)", path.display(), msg);
+                    e.seen.fill(false);
+                }
+            }
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => eprintln!("{}: {}", path.display(), err),
+        }
+        e
+    }
+
+    /// Escribe `seen` en la carpeta de configuración (los niveles aleatorios no)
+    pub fn save(&self) -> io::Result<()> {
+        let Some(level) = self.level.as_ref().filter(|l| is_persistent(l)) else { return Ok(()); };
+        let path = file_for(level).ok_or_else(|| io::Error::other("no hay carpeta de configuración (HOME/APPDATA)"))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_text())
+    }
+
+    /// Olvida todo lo visto del nivel, también lo guardado
+    pub fn reset(&mut self) -> io::Result<()> {
+        self.seen.fill(false);
+        self.visible.fill(false);
+        let Some(path) = self.level.as_ref().filter(|l| is_persistent(l)).and_then(file_for) else { return Ok(()); };
+        match fs::remove_file(path) {
+            Err(err) if err.kind() != io::ErrorKind::NotFound => Err(err),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn level(&self) -> Option<&LevelSource> {
+        self.level.as_ref()
+    }
+
+    /// Al empezar a dibujar un frame: lo visible y los rayos se vuelven a calcular
+    pub fn begin_frame(&mut self, eye: Vector2) {
+        self.visible.fill(false);
+        self.eye = eye;
+        self.rays.clear();
+    }
+
+    /// Punto donde terminó un rayo (la pared que tocó o su alcance máximo)
+    pub fn add_ray(&mut self, end: Vector2) {
+        self.rays.push(end);
+    }
+
+    /// Origen y extremos de los rayos del último frame
+    pub fn view_rays(&self) -> (Vector2, &[Vector2]) {
+        (self.eye, &self.rays)
+    }
+
+    /// Celda alcanzada por un rayo en este frame
+    pub fn mark(&mut self, i: i32, j: i32) {
+        if let Some(k) = self.index(i, j) {
+            self.seen[k] = true;
+            self.visible[k] = true;
+        }
+    }
+
+    pub fn is_seen(&self, i: i32, j: i32) -> bool {
+        self.index(i, j).is_some_and(|k| self.seen[k])
+    }
+
+    pub fn is_visible(&self, i: i32, j: i32) -> bool {
+        self.index(i, j).is_some_and(|k| self.visible[k])
+    }
+
+    fn index(&self, i: i32, j: i32) -> Option<usize> {
+        let (i, j) = (usize::try_from(i).ok()?, usize::try_from(j).ok()?);
+        (i < self.width && j < self.height).then_some(j * self.width + i)
+    }
+
+    fn to_text(&self) -> String {
+        let mut out = format!("{}\nsize {} {}\n", MAGIC, self.width, self.height);
+        for row in self.seen.chunks(self.width.max(1)) {
+            let line: String = row.iter().map(|&s| if s { '#' } else { '.' }).collect();
+            let _ = writeln!(out, "{}", line);
+        }
+        out
+    }
+
+    fn parse_seen(&mut self, text: &str) -> Result<(), String> {
+        let mut lines = text.lines().filter(|l| !l.trim().is_empty());
+        if lines.next().map(str::trim) != Some(MAGIC) {
+            return Err(format!("falta '{}'", MAGIC));
+        }
+        let size = lines.next().and_then(|l| l.strip_prefix("size ")).ok_or("falta la línea 'size'")?;
+        if size.split_whitespace().collect::<Vec<_>>() != [self.width.to_string(), self.height.to_string()] {
+            return Err(format!("tamaño {} distinto del laberinto ({} {})", size, self.width, self.height));
+        }
+        for (j, line) in lines.take(self.height).enumerate() {
+            for (i, c) in line.trim().chars().take(self.width).enumerate() {
+                self.seen[j * self.width + i] = c == '#';
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_persistent(level: &LevelSource) -> bool {
+    matches!(level, LevelSource::File(_))
+}
+
+// Un archivo por nivel: `levels/01.txt` → `levels_01_txt.map`
+fn file_for(level: &LevelSource) -> Option<PathBuf> {
+    let name: String = level.id().chars().map(|c| if c.is_ascii_alphanumeric() { c } else { '_' }).collect();
+    config::config_path(&format!("{}/{}.map", DIR, name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(cols: usize, rows: usize) -> Maze {
+        Maze::parse(&vec!["#".repeat(cols); rows].join("\n"), 64)
+    }
+
+    #[test]
+    fn seen_cells_survive_save_and_load() {
+        let m = maze(5, 3);
+        let mut saved = Explored::unsaved(&m);
+        for (i, j) in [(0, 0), (3, 1), (4, 2)] {
+            saved.mark(i, j);
+        }
+        let text = saved.to_text();
+
+        let mut loaded = Explored::unsaved(&m);
+        loaded.parse_seen(&text).unwrap();
+        assert_eq!(loaded.seen, saved.seen);
+        assert!(loaded.is_seen(3, 1) && !loaded.is_seen(1, 1));
+        assert!(!loaded.is_visible(3, 1)); // lo visible es del frame, no se guarda
+    }
+
+    #[test]
+    fn grid_of_another_size_is_rejected() {
+        let mut saved = Explored::unsaved(&maze(5, 3));
+        saved.mark(1, 1);
+        let text = saved.to_text();
+
+        let mut other = Explored::unsaved(&maze(3, 5));
+        assert!(other.parse_seen(&text).is_err());
+        assert!(other.seen.iter().all(|&s| !s));
+    }
+}
@@ -1,9 +1,106 @@
 use raylib::prelude::*;
+use raylib::ffi;
+use std::ffi::CString;
 
-pub struct FrameBuffer;
+/// Buffer de píxeles en CPU. Cada píxel es RGBA8 empacado en un u32
+/// (en memoria queda r,g,b,a en ese orden, listo para subir a una textura).
+pub struct FrameBuffer {
+    width: i32,
+    height: i32,
+    pixels: Vec<u32>,
+}
 
 impl FrameBuffer {
-    pub fn new() -> Self { Self }
-    pub fn clear(&mut self, _d: &mut RaylibDrawHandle, _color: Color) {}
-    pub fn draw_vertical_line(_d: &mut RaylibDrawHandle, _x: i32, _y0: i32, _y1: i32, _color: Color) {}
+    pub fn new(width: i32, height: i32) -> Self {
+        let (w, h) = (width.max(1), height.max(1));
+        Self { width: w, height: h, pixels: vec![pack(Color::BLACK); (w * h) as usize] }
+    }
+
+    pub fn width(&self) -> i32 { self.width }
+    pub fn height(&self) -> i32 { self.height }
+
+    pub fn clear(&mut self, color: Color) {
+        self.pixels.fill(pack(color));
+    }
+
+    #[inline]
+    pub fn set_pixel(&mut self, x: i32, y: i32, color: Color) {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height { return; }
+        self.pixels[(y * self.width + x) as usize] = pack(color);
+    }
+
+    #[inline]
+    pub fn get_pixel(&self, x: i32, y: i32) -> Color {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height { return Color::BLANK; }
+        unpack(self.pixels[(y * self.width + x) as usize])
+    }
+
+    /// Línea vertical [y0, y1) recortada a la pantalla
+    pub fn draw_vertical_line(&mut self, x: i32, y0: i32, y1: i32, color: Color) {
+        if x < 0 || x >= self.width { return; }
+        let (y0, y1) = (y0.max(0), y1.min(self.height));
+        let c = pack(color);
+        for y in y0..y1 {
+            self.pixels[(y * self.width + x) as usize] = c;
+        }
+    }
+
+    /// Vista en bytes RGBA (formato R8G8B8A8 de raylib)
+    pub fn as_bytes(&self) -> &[u8] {
+        // u32 -> 4 u8: mismo buffer, sin copiar; pack() usa el orden nativo
+        unsafe { std::slice::from_raw_parts(self.pixels.as_ptr() as *const u8, self.pixels.len() * 4) }
+    }
+
+    /// Copia el contenido a una Image de raylib (solo CPU, no necesita ventana)
+    pub fn to_image(&self) -> Image {
+        let img = Image::gen_image_color(self.width, self.height, Color::BLACK);
+        let bytes = self.as_bytes();
+        // gen_image_color crea una imagen R8G8B8A8 del mismo tamaño
+        unsafe { std::ptr::copy_nonoverlapping(bytes.as_ptr(), img.data() as *mut u8, bytes.len()); }
+        img
+    }
+
+    /// Guarda el framebuffer como PNG. Retorna false si raylib no pudo escribirlo.
+    pub fn save_png(&self, path: &str) -> bool {
+        let Ok(c_path) = CString::new(path) else { return false; };
+        let img = self.to_image();
+        unsafe { ffi::ExportImage(*img, c_path.as_ptr()) }
+    }
+}
+
+/// Textura de GPU del tamaño del framebuffer; recibe los píxeles una vez por frame
+pub struct FrameTexture {
+    tex: Texture2D,
+}
+
+impl FrameTexture {
+    pub fn new(rl: &mut RaylibHandle, thread: &RaylibThread, fb: &FrameBuffer) -> Self {
+        let img = Image::gen_image_color(fb.width(), fb.height(), Color::BLACK);
+        let tex = rl.load_texture_from_image(thread, &img)
+            .expect("No se pudo crear la textura del framebuffer");
+        Self { tex }
+    }
+
+    /// Sube el framebuffer y lo dibuja estirado a toda la pantalla
+    pub fn present(&mut self, d: &mut RaylibDrawHandle, fb: &FrameBuffer) {
+        let _ = self.tex.update_texture(fb.as_bytes());
+        let (sw, sh) = (d.get_screen_width() as f32, d.get_screen_height() as f32);
+        d.draw_texture_pro(
+            &self.tex,
+            Rectangle { x: 0.0, y: 0.0, width: fb.width() as f32, height: fb.height() as f32 },
+            Rectangle { x: 0.0, y: 0.0, width: sw, height: sh },
+            Vector2::zero(), 0.0, Color::WHITE
+        );
+    }
+}
+
+#[inline]
+fn pack(c: Color) -> u32 {
+    u32::from_ne_bytes([c.r, c.g, c.b, c.a])
+}
+
+#[inline]
+fn unpack(p: u32) -> Color {
+    let [r, g, b, a] = p.to_ne_bytes();
+    Color::new(r, g, b, a)
 }
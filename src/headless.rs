@@ -0,0 +1,169 @@
+use raylib::prelude::*;
+
+use crate::caster::{render_3d_scene, LevelTextures, SceneTextures, Sprite, DEFAULT_FOV};
+use crate::entity::{SpriteSheet, World};
+use crate::framebuffer::FrameBuffer;
+use crate::levels::load_level_file;
+use crate::maze::Maze;
+use crate::player::{spawn_player_from_maze, Player};
+
+/// Opciones del modo sin ventana:
+/// `--render-png SALIDA --level RUTA [--pos X,Y] [--angle GRADOS] [--size ANCHOxALTO]`
+/// (`--pos` en celdas; por defecto se usa la 'P' del mapa)
+pub struct HeadlessArgs {
+    pub out: String,
+    pub level: String,
+    pub pos: Option<Vector2>,
+    pub angle_deg: Option<f32>,
+    pub width: i32,
+    pub height: i32,
+}
+
+impl HeadlessArgs {
+    /// None si no se pidió `--render-png` (arranque normal con ventana)
+    pub fn parse(args: &[String]) -> Option<Result<Self, String>> {
+        if !args.iter().any(|a| a == "--render-png") {
+            return None;
+        }
+        Some(Self::parse_inner(args))
+    }
+
+    fn parse_inner(args: &[String]) -> Result<Self, String> {
+        let mut out = None;
+        let mut level = None;
+        let mut pos = None;
+        let mut angle_deg = None;
+        let (mut width, mut height) = (1024, 640);
+
+        let mut it = args.iter();
+        while let Some(flag) = it.next() {
+            let mut value = || it.next().cloned().ok_or(format!("Falta el valor de {}", flag));
+            match flag.as_str() {
+                "--render-png" => out = Some(value()?),
+                "--level" => level = Some(value()?),
+                "--pos" => {
+                    let v = value()?;
+                    let (x, y) = v.split_once(',').ok_or(format!("--pos inválido: {}", v))?;
+                    pos = Some(Vector2::new(parse_num(x)?, parse_num(y)?));
+                }
+                "--angle" => angle_deg = Some(parse_num(&value()?)?),
+                "--size" => {
+                    let v = value()?;
+                    let (w, h) = v.split_once('x').ok_or(format!("--size inválido: {}", v))?;
+                    width = w.trim().parse().map_err(|_| format!("--size inválido: {}", v))?;
+                    height = h.trim().parse().map_err(|_| format!("--size inválido: {}", v))?;
+                }
+                other => return Err(format!("Argumento desconocido: {}", other)),
+            }
+        }
+
+        Ok(Self {
+            out: out.ok_or("Falta --render-png SALIDA")?,
+            level: level.ok_or("Falta --level RUTA")?,
+            pos,
+            angle_deg,
+            width,
+            height,
+        })
+    }
+}
+
+fn parse_num(s: &str) -> Result<f32, String> {
+    s.trim().parse().map_err(|_| format!("Número inválido: {}", s))
+}
+
+/// Rasteriza un frame en CPU, sin abrir ventana ni usar la GPU
+pub fn render_frame(
+    maze: &Maze,
+    player: &Player,
+    tex: &SceneTextures,
+    sprites: &[Sprite],
+    width: i32,
+    height: i32,
+) -> FrameBuffer {
+    let mut fb = FrameBuffer::new(width, height);
+    render_3d_scene(&mut fb, maze, player, tex, sprites, DEFAULT_FOV, None);
+    fb
+}
+
+/// `render_frame` guardado como PNG
+pub fn render_to_png(
+    maze: &Maze,
+    player: &Player,
+    tex: &SceneTextures,
+    sprites: &[Sprite],
+    width: i32,
+    height: i32,
+    path: &str,
+) -> bool {
+    render_frame(maze, player, tex, sprites, width, height).save_png(path)
+}
+
+/// Carga nivel y texturas según los argumentos y escribe el PNG
+pub fn run(args: &HeadlessArgs) -> Result<(), String> {
+    let (maze, meta) = load_level_file(&args.level)
+        .map_err(|e| format!("No se pudo leer {}: {}", args.level, e))?;
+
+    let mut player = spawn_player_from_maze(&maze);
+    if let Some(cell) = args.pos {
+        let bs = maze.block_size() as f32;
+        player.pos = Vector2::new(cell.x * bs, cell.y * bs);
+    }
+    if let Some(deg) = args.angle_deg {
+        player.a = deg.to_radians();
+    }
+
+    let level_tex = LevelTextures::load(&meta)?;
+    let sheet = SpriteSheet::load()?;
+    let tex = level_tex.scene();
+
+    // entidades en su posición inicial
+    let world = World::spawn_from_maze(&maze, &meta);
+    let sprites = world.sprites(&sheet, 1.0);
+
+    if render_to_png(&maze, &player, &tex, &sprites, args.width, args.height, &args.out) {
+        Ok(())
+    } else {
+        Err(format!("No se pudo escribir {}", args.out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::caster::{PlaneTextures, TexPixels};
+
+    // un color por superficie; la niebla negra solo los oscurece
+    fn textures() -> (TexPixels, TexPixels, PlaneTextures) {
+        let wall = TexPixels::solid(Color::new(200, 0, 0, 255));
+        let door = TexPixels::solid(Color::new(200, 200, 0, 255));
+        let planes = PlaneTextures {
+            floor: TexPixels::solid(Color::new(0, 200, 0, 255)),
+            ceiling: TexPixels::solid(Color::new(0, 0, 200, 255)),
+            sky: TexPixels::solid(Color::WHITE),
+        };
+        (wall, door, planes)
+    }
+
+    #[test]
+    fn wall_column_between_ceiling_and_floor() {
+        // pasillo mirando al este: la pared del fondo queda a 2,5 celdas
+        let maze = Maze::parse("#####\n#P  #\n#####\n", 64);
+        let player = Player::new(maze.cell_center_world((1, 1)));
+        let (wall, door, planes) = textures();
+        let tex = SceneTextures { wall: &wall, door: &door, planes: &planes, fog: Color::BLACK };
+        let (w, h) = (64, 40);
+        let fb = render_frame(&maze, &player, &tex, &[], w, h);
+
+        let mid = fb.get_pixel(w / 2, h / 2);
+        assert!(mid.r > 0 && mid.g == 0 && mid.b == 0, "pared: {:?}", mid);
+        let top = fb.get_pixel(w / 2, 0);
+        assert!(top.b > 0 && top.r == 0 && top.g == 0, "techo: {:?}", top);
+        let bottom = fb.get_pixel(w / 2, h - 1);
+        assert!(bottom.g > 0 && bottom.r == 0 && bottom.b == 0, "piso: {:?}", bottom);
+
+        // la columna mide alto / 2,5 = 16 filas, centrada: de la 12 a la 27
+        let is_wall = |y| fb.get_pixel(w / 2, y).r > 0;
+        assert!(!is_wall(11) && is_wall(12) && is_wall(27) && !is_wall(28));
+    }
+}
@@ -0,0 +1,71 @@
+use raylib::prelude::*;
+use raylib::consts::GamepadAxis;
+
+use crate::controls::{Controls, InputAction};
+
+/// Entrada de un frame, ya traducida a intenciones. La simulación solo ve
+/// esto; `read` es el único lugar que consulta teclado, mouse y mando.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct InputSnapshot {
+    pub forward: f32,   // -1..1 (avanzar/retroceder, stick izquierdo)
+    pub strafe: f32,    // -1..1 (pasos laterales, stick izquierdo)
+    pub turn: f32,      // rad/s (girar, stick derecho)
+    pub look: f32,      // radianes a girar este frame (mouse; no depende de dt)
+    pub sprint: bool,
+    pub use_pressed: bool, // acción "usar" (flanco)
+    pub hint_pressed: bool, // pedir una pista (flanco); main lo apaga sin las ayudas
+}
+
+impl InputSnapshot {
+    /// Adaptador raylib: lee el estado actual de los dispositivos según el mapa de `controls`
+    pub fn read(rl: &RaylibHandle, controls: &Controls) -> Self {
+        let turn_sign = if controls.invert_turn { -1.0 } else { 1.0 };
+        let mut s = Self {
+            look: rl.get_mouse_delta().x * controls.mouse_sensitivity * turn_sign,
+            ..Self::default()
+        };
+
+        // ---- rotación digital: teclado (← / →) o D-Pad, más fino ----
+        let turn_speed_keys: f32 = 2.8; // rad/s
+        let turn_speed_dpad: f32 = 2.6;
+        for (action, sign) in [(InputAction::TurnLeft, -1.0), (InputAction::TurnRight, 1.0)] {
+            let b = controls.binding(action);
+            if b.key.is_some_and(|k| rl.is_key_down(k)) {
+                s.turn += sign * turn_speed_keys;
+            } else if b.button.is_some_and(|btn| rl.is_gamepad_button_down(0, btn)) {
+                s.turn += sign * turn_speed_dpad;
+            }
+        }
+
+        // ---- movimiento digital ----
+        if controls.down(rl, InputAction::MoveForward) { s.forward += 1.0; }
+        if controls.down(rl, InputAction::MoveBack)    { s.forward -= 1.0; }
+        if controls.down(rl, InputAction::StrafeRight) { s.strafe += 1.0; }
+        if controls.down(rl, InputAction::StrafeLeft)  { s.strafe -= 1.0; }
+
+        s.sprint = controls.down(rl, InputAction::Sprint);
+        s.use_pressed = controls.pressed(rl, InputAction::Use);
+        s.hint_pressed = controls.pressed(rl, InputAction::Hint);
+
+        if rl.is_gamepad_available(0) {
+            let dz = controls.deadzone;
+
+            // stick derecho: velocidad de giro escalada por el eje
+            let rx = deadzone(rl.get_gamepad_axis_movement(0, GamepadAxis::GAMEPAD_AXIS_RIGHT_X), dz);
+            let turn_speed_gamepad = 3.6;
+            s.turn += rx * turn_speed_gamepad * turn_sign;
+
+            // stick izquierdo (en la mayoría de mandos, arriba = -1, abajo = +1)
+            let lx = deadzone(rl.get_gamepad_axis_movement(0, GamepadAxis::GAMEPAD_AXIS_LEFT_X), dz);
+            let ly = deadzone(rl.get_gamepad_axis_movement(0, GamepadAxis::GAMEPAD_AXIS_LEFT_Y), dz);
+            s.forward += if controls.invert_move { ly } else { -ly };
+            s.strafe += lx;
+        }
+        s
+    }
+}
+
+// Deadzone simétrica para sticks analógicos
+fn deadzone(v: f32, dz: f32) -> f32 {
+    if v.abs() < dz { 0.0 } else { v }
+}
@@ -1,42 +1,155 @@
 use std::fs;
 use std::path::{Path, PathBuf};
 
+use crate::maze::generate::{generate, Algorithm, GenOptions};
 use crate::maze::Maze;
+use crate::validate;
+
+/// Ajustes del screamer de un nivel
+#[derive(Clone)]
+pub struct ScreamerCfg {
+    pub enabled: bool,  // false = se ignora la 'S' del mapa
+    pub range: f32,     // distancia a la que atrapa al jugador, en celdas
+    pub duration: f32,  // segundos que dura el susto antes del game over
+}
+
+impl Default for ScreamerCfg {
+    fn default() -> Self {
+        Self { enabled: true, range: 0.7, duration: 1.2 }
+    }
+}
+
+/// Metadatos de un nivel. Salen de la cabecera opcional del .txt: líneas
+/// `@clave valor` antes del grid. Sin cabecera se usan los valores por defecto.
+///
+/// ```text
+/// @name El sótano
+/// @author Erick
+/// @par 1:30
+/// @music sounds/music.ogg
+/// @wall assets/wall.png
+/// @door assets/door.png
+/// @floor assets/floor.png
+/// @ceiling assets/ceiling.png
+/// @sky assets/sky.png
+/// @fog 20,10,10
+/// @screamer on
+/// @screamer_range 0.8
+/// @screamer_time 2
+/// ```
+#[derive(Clone)]
+pub struct LevelMeta {
+    pub name: String,
+    pub author: Option<String>,
+    pub par_time: Option<f32>, // segundos
+    pub music: String,
+    pub wall: String,
+    pub door: String,
+    pub floor: String,
+    pub ceiling: String,
+    pub sky: Option<String>,
+    pub fog: (u8, u8, u8), // color hacia el que se oscurece con la distancia
+    pub screamer: ScreamerCfg,
+}
+
+impl LevelMeta {
+    /// Valores por defecto; el nombre es el del archivo
+    pub fn with_name(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            author: None,
+            par_time: None,
+            music: "sounds/music.ogg".to_string(),
+            wall: "assets/wall.png".to_string(),
+            door: "assets/door.png".to_string(),
+            floor: "assets/floor.png".to_string(),
+            ceiling: "assets/ceiling.png".to_string(),
+            sky: None,
+            fog: (0, 0, 0),
+            screamer: ScreamerCfg::default(),
+        }
+    }
+}
+
+/// De dónde sale un nivel: alcanza para volver a armarlo igual (repeticiones)
+#[derive(Clone, Debug, PartialEq)]
+pub enum LevelSource {
+    File(PathBuf),
+    Random { seed: u64, algorithm: Algorithm, cols: usize, rows: usize },
+}
+
+impl LevelSource {
+    pub fn load(&self) -> std::io::Result<(Maze, LevelMeta)> {
+        match self {
+            LevelSource::File(path) => load_level_file(path),
+            &LevelSource::Random { seed, algorithm, cols, rows } => {
+                let opts = GenOptions { cols, rows, algorithm, screamer: true };
+                let maze = generate(seed, &opts, 64); // mismo tamaño de celda que los .txt
+                let name = format!("Aleatorio #{} ({}, {}x{})", seed, algorithm.name(), cols, rows);
+                Ok((maze, LevelMeta::with_name(&name)))
+            }
+        }
+    }
+
+    /// Identificador sin la semilla: la ruta del .txt o `random:ALGORITMO:COLSxFILAS`
+    pub fn id(&self) -> String {
+        match self {
+            LevelSource::File(path) => path.display().to_string(),
+            LevelSource::Random { algorithm, cols, rows, .. } => format!("random:{}:{}x{}", algorithm.name(), cols, rows),
+        }
+    }
+
+    /// Semilla del generador (0 en los niveles de archivo)
+    pub fn seed(&self) -> u64 {
+        match self {
+            LevelSource::File(_) => 0,
+            LevelSource::Random { seed, .. } => *seed,
+        }
+    }
+
+    /// Inversa de `id` + `seed`
+    pub fn from_id(id: &str, seed: u64) -> Option<Self> {
+        let Some(rest) = id.strip_prefix("random:") else {
+            return Some(LevelSource::File(PathBuf::from(id)));
+        };
+        let (algo, size) = rest.split_once(':')?;
+        let (cols, rows) = size.split_once('x')?;
+        Some(LevelSource::Random {
+            seed,
+            algorithm: Algorithm::ALL.into_iter().find(|a| a.name() == algo)?,
+            cols: cols.parse().ok()?,
+            rows: rows.parse().ok()?,
+        })
+    }
+}
 
 pub struct Levels {
     maps: Vec<Maze>,
-    names: Vec<String>,
+    metas: Vec<LevelMeta>,
+    sources: Vec<LevelSource>,
+    file_count: usize, // los de ./levels; después puede venir un nivel generado
     current: usize,
 }
 
 impl Levels {
     pub fn load_from_dir<P: AsRef<Path>>(dir: P) -> std::io::Result<Self> {
-        let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
-            .filter_map(|e| e.ok().map(|e| e.path()))
-            .filter(|p| p.is_file())
-            .filter(|p| p.extension().map(|e| e.eq_ignore_ascii_case("txt")).unwrap_or(false))
-            .collect();
-
-        // 01.txt, 02.txt, ...
-        entries.sort();
-
         let mut maps = Vec::new();
-        let mut names = Vec::new();
+        let mut metas = Vec::new();
+        let mut sources = Vec::new();
 
-        for path in entries {
-            let text = fs::read_to_string(&path)?;
-            let maze = Maze::from_str_map(&text, 64); // tamaño de celda en pixeles
+        for path in level_files(dir)? {
+            let (maze, meta) = load_level_file(&path)?;
+            // se carga igual; `lint-levels` da el detalle completo
+            for issue in validate::check_maze(&maze) {
+                eprintln!("{}: {}", path.display(), issue);
+            }
             maps.push(maze);
-
-            let name = path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("nivel")
-                .to_string();
-            names.push(name);
+            metas.push(meta);
+            sources.push(LevelSource::File(path));
         }
 
-        Ok(Self { maps, names, current: 0 })
+        let file_count = maps.len();
+        Ok(Self { maps, metas, sources, file_count, current: 0 })
     }
 
     /// Nivel activo (solo lectura)
@@ -47,12 +160,14 @@ impl Levels {
     /// Índice del nivel activo
     pub fn index(&self) -> usize { self.current }
 
-    /// Cantidad de niveles
-    pub fn len(&self) -> usize { self.maps.len() }
+    /// Cantidad de niveles cargados de archivo (sin contar el aleatorio)
+    pub fn len(&self) -> usize { self.file_count }
+
+    pub fn is_empty(&self) -> bool { self.file_count == 0 }
 
     /// Avanza al siguiente nivel. Retorna true si avanzó; false si ya no hay más.
-    pub fn next(&mut self) -> bool {
-        if self.current + 1 < self.maps.len() {
+    pub fn next_level(&mut self) -> bool {
+        if self.current + 1 < self.file_count {
             self.current += 1;
             true
         } else {
@@ -69,8 +184,196 @@ impl Levels {
         }
     }
 
-    /// Nombre legible del nivel `idx` (tomado del nombre del archivo sin extensión)
+    /// Nombre legible del nivel `idx` (`@name`, o el nombre del archivo sin extensión)
     pub fn name(&self, idx: usize) -> &str {
-        self.names.get(idx).map(|s| s.as_str()).unwrap_or("nivel")
+        self.metas.get(idx).map(|m| m.name.as_str()).unwrap_or("nivel")
+    }
+
+    /// Arma un nivel en el lugar del aleatorio (reemplaza al anterior) y
+    /// retorna su índice, para pasarlo a `set_current`
+    pub fn set_generated(&mut self, source: LevelSource) -> std::io::Result<usize> {
+        let (maze, meta) = source.load()?;
+        self.maps.truncate(self.file_count);
+        self.metas.truncate(self.file_count);
+        self.sources.truncate(self.file_count);
+        self.maps.push(maze);
+        self.metas.push(meta);
+        self.sources.push(source);
+        Ok(self.file_count)
+    }
+
+    /// true si el nivel activo es el generado
+    pub fn is_generated(&self) -> bool { self.current >= self.file_count }
+
+    /// Origen del nivel activo
+    pub fn source(&self) -> &LevelSource {
+        &self.sources[self.current]
+    }
+
+    /// Metadatos del nivel activo
+    pub fn meta(&self) -> &LevelMeta {
+        &self.metas[self.current]
+    }
+
+    /// Metadatos del nivel `idx`
+    pub fn meta_at(&self, idx: usize) -> Option<&LevelMeta> {
+        self.metas.get(idx)
+    }
+}
+
+/// Archivos .txt de `dir`, ordenados por nombre (01.txt, 02.txt, ...)
+pub fn level_files<P: AsRef<Path>>(dir: P) -> std::io::Result<Vec<PathBuf>> {
+    let mut entries: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .filter(|p| p.extension().map(|e| e.eq_ignore_ascii_case("txt")).unwrap_or(false))
+        .collect();
+    entries.sort();
+    Ok(entries)
+}
+
+/// Carga un único archivo de nivel (cabecera `@` opcional + grid)
+pub fn load_level_file<P: AsRef<Path>>(path: P) -> std::io::Result<(Maze, LevelMeta)> {
+    let path = path.as_ref();
+    let text = fs::read_to_string(path)?;
+    let stem = path.file_stem().and_then(|s| s.to_str()).unwrap_or("nivel");
+    let (meta, grid, _) = parse_header(&text, stem);
+    Ok((Maze::parse(&grid, 64), meta)) // tamaño de celda en pixeles
+}
+
+/// Separa la cabecera (líneas `@clave valor` al inicio) del grid del mapa.
+/// Claves desconocidas o valores inválidos se ignoran y queda el valor por defecto.
+/// El tercer valor es cuántas líneas ocupó la cabecera (el grid arranca en la siguiente).
+pub fn parse_header(text: &str, default_name: &str) -> (LevelMeta, String, usize) {
+    let mut meta = LevelMeta::with_name(default_name);
+    let mut lines = text.lines().peekable();
+    let mut header_lines = 0;
+
+    while let Some(line) = lines.peek() {
+        let Some(rest) = line.trim_start().strip_prefix('@') else { break; };
+        lines.next();
+        header_lines += 1;
+
+        let (key, value) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+        let value = value.trim();
+        match key {
+            "name" => meta.name = value.to_string(),
+            "author" => meta.author = Some(value.to_string()),
+            "par" => meta.par_time = parse_time(value).or(meta.par_time),
+            "music" => meta.music = value.to_string(),
+            "wall" => meta.wall = value.to_string(),
+            "door" => meta.door = value.to_string(),
+            "floor" => meta.floor = value.to_string(),
+            "ceiling" => meta.ceiling = value.to_string(),
+            "sky" => meta.sky = Some(value.to_string()),
+            "fog" => meta.fog = parse_rgb(value).unwrap_or(meta.fog),
+            "screamer" => meta.screamer.enabled = !matches!(value, "off" | "no" | "0" | "false"),
+            "screamer_range" => meta.screamer.range = value.parse().unwrap_or(meta.screamer.range),
+            "screamer_time" => meta.screamer.duration = value.parse().unwrap_or(meta.screamer.duration),
+            _ => {}
+        }
+    }
+
+    let grid: Vec<&str> = lines.collect();
+    (meta, grid.join("\n"), header_lines)
+}
+
+/// "90" (segundos) o "1:30" (minutos:segundos)
+fn parse_time(s: &str) -> Option<f32> {
+    match s.split_once(':') {
+        Some((m, sec)) => Some(m.trim().parse::<f32>().ok()? * 60.0 + sec.trim().parse::<f32>().ok()?),
+        None => s.parse().ok(),
+    }
+}
+
+/// "r,g,b" o "#rrggbb"
+fn parse_rgb(s: &str) -> Option<(u8, u8, u8)> {
+    if let Some(hex) = s.strip_prefix('#') {
+        if hex.len() != 6 { return None; }
+        let v = u32::from_str_radix(hex, 16).ok()?;
+        return Some(((v >> 16) as u8, (v >> 8) as u8, v as u8));
+    }
+    let mut it = s.split(',').map(|c| c.trim().parse::<u8>());
+    match (it.next(), it.next(), it.next(), it.next()) {
+        (Some(Ok(r)), Some(Ok(g)), Some(Ok(b)), None) => Some((r, g, b)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_keys() {
+        let text = "\
+@name El sótano
+@author Erick
+@par 1:30
+@music sounds/otra.ogg
+@wall a/w.png
+@door a/d.png
+@floor a/f.png
+@ceiling a/c.png
+@sky a/s.png
+@fog 20, 10,10
+@screamer off
+@screamer_range 0.8
+@screamer_time 2
+@clave_rara lo que sea
+#####
+#P D#
+#####";
+        let (meta, grid, lines) = parse_header(text, "01");
+        assert_eq!(meta.name, "El sótano");
+        assert_eq!(meta.author.as_deref(), Some("Erick"));
+        assert_eq!(meta.par_time, Some(90.0));
+        assert_eq!(meta.music, "sounds/otra.ogg");
+        assert_eq!((&*meta.wall, &*meta.door, &*meta.floor, &*meta.ceiling), ("a/w.png", "a/d.png", "a/f.png", "a/c.png"));
+        assert_eq!(meta.sky.as_deref(), Some("a/s.png"));
+        assert_eq!(meta.fog, (20, 10, 10));
+        assert!(!meta.screamer.enabled);
+        assert_eq!((meta.screamer.range, meta.screamer.duration), (0.8, 2.0));
+        assert_eq!(grid, "#####\n#P D#\n#####");
+        assert_eq!(lines, 14);
+    }
+
+    #[test]
+    fn no_header_keeps_defaults_and_the_file_name() {
+        let (meta, grid, lines) = parse_header("###\n#P#", "03");
+        assert_eq!(meta.name, "03");
+        assert_eq!(meta.par_time, None);
+        assert_eq!(meta.fog, (0, 0, 0));
+        assert!(meta.screamer.enabled);
+        assert_eq!((grid.as_str(), lines), ("###\n#P#", 0));
+    }
+
+    #[test]
+    fn bad_values_keep_defaults() {
+        let (meta, _, _) = parse_header("@par pronto\n@fog 300,0,0\n@screamer_range lejos\n#", "x");
+        let default = LevelMeta::with_name("x");
+        assert_eq!(meta.par_time, None);
+        assert_eq!(meta.fog, default.fog);
+        assert_eq!(meta.screamer.range, default.screamer.range);
+    }
+
+    #[test]
+    fn par_time_formats() {
+        assert_eq!(parse_time("90"), Some(90.0));
+        assert_eq!(parse_time("0:45"), Some(45.0));
+        assert_eq!(parse_time("2:05.5"), Some(125.5));
+        assert_eq!(parse_time("1:xx"), None);
+        assert_eq!(parse_time(""), None);
+    }
+
+    #[test]
+    fn fog_colors() {
+        assert_eq!(parse_rgb("20,10,10"), Some((20, 10, 10)));
+        assert_eq!(parse_rgb(" 1 , 2 , 3 "), Some((1, 2, 3)));
+        assert_eq!(parse_rgb("#ff8000"), Some((255, 128, 0)));
+        assert_eq!(parse_rgb("#fff"), None);
+        assert_eq!(parse_rgb("1,2"), None);
+        assert_eq!(parse_rgb("1,2,3,4"), None);
+        assert_eq!(parse_rgb("256,0,0"), None);
     }
 }
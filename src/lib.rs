@@ -0,0 +1,22 @@
+// Módulos compartidos entre el juego (main.rs) y las herramientas de src/bin
+pub mod tile;
+pub mod maze;
+pub mod levels;
+pub mod player;
+pub mod caster;
+pub mod minimap;
+pub mod framebuffer;
+pub mod line;
+pub mod headless;
+pub mod validate;
+pub mod screamer;
+pub mod entity;
+pub mod input;
+pub mod config;
+pub mod controls;
+pub mod settings;
+pub mod sim;
+pub mod replay;
+pub mod audio;
+pub mod explored;
+pub mod assist;
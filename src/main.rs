@@ -1,67 +1,200 @@
-mod maze;
-mod levels;
-mod player;
-mod caster;
-mod minimap;
-mod framebuffer;
-mod line;
-
 use raylib::prelude::*;
-use raylib::ffi;
-use std::ffi::CString;
 
-use crate::levels::Levels;
-use crate::player::Player;
-use crate::caster::render_3d_scene;
-use crate::minimap::{draw_minimap, MiniMapCfg};
-use crate::maze::Maze;
+use proyecto_1::headless;
+use proyecto_1::audio::{Audio, Category, Spatial};
+use proyecto_1::levels::{LevelSource, Levels};
+use proyecto_1::input::InputSnapshot;
+use proyecto_1::controls::{binding_text, button_name, key_name, Binding, Controls, InputAction};
+use proyecto_1::settings::{SettingRow, Settings};
+use proyecto_1::sim::{FixedStep, Sim, SimEvent, TICK};
+use proyecto_1::caster::{render_3d_scene, LevelTextures};
+use proyecto_1::framebuffer::{FrameBuffer, FrameTexture};
+use proyecto_1::minimap::{self, draw_full_map, draw_minimap, Corner, Guide, MiniMapCfg};
+use proyecto_1::explored::Explored;
+use proyecto_1::maze::Maze;
+use proyecto_1::maze::generate::Algorithm;
+use proyecto_1::entity::SpriteSheet;
+use proyecto_1::replay::{Ending, Outcome, Replay};
+
+// tamaños del laberinto aleatorio (nombre, columnas, filas en celdas)
+const GEN_SIZES: [(&str, usize, usize); 3] = [("chico", 8, 6), ("mediano", 14, 10), ("grande", 22, 16)];
 
 #[derive(Clone, Copy, PartialEq, Eq)]
 enum AppState {
     Menu,
     Playing,
     WonLevel,
+    GameOver,
+    Replay,   // se muestra la última partida grabada
+    Controls, // pantalla para reasignar teclas/botones
+    Settings, // opciones de video, audio y juego
+    Paused,   // nivel congelado con el menú de pausa encima
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum PauseItem {
+    Resume,
+    Restart,
+    Settings,
+    Controls,
+    ForgetMap,
+    LevelSelect,
+    Quit,
+}
+
+const PAUSE_ITEMS: [(PauseItem, &str); 7] = [
+    (PauseItem::Resume, "Continuar"),
+    (PauseItem::Restart, "Reiniciar nivel"),
+    (PauseItem::Settings, "Opciones"),
+    (PauseItem::Controls, "Controles"),
+    (PauseItem::ForgetMap, "Olvidar el mapa del nivel"),
+    (PauseItem::LevelSelect, "Elegir nivel"),
+    (PauseItem::Quit, "Salir del juego"),
+];
+
+// reasignando una acción: sin tecla ni botón en este tiempo se cancela solo
+const REBIND_SECONDS: f32 = 5.0;
+
+// la última partida se guarda acá (también sirve para adjuntar a un reporte de bug)
+const LAST_REPLAY: &str = "replays/last.replay";
+
+/// Para qué se dibuja el nivel (`draw_level_view`): de eso depende qué mapa de
+/// lo explorado marcan los rayos. El f32 es el `alpha` de `FixedStep::alpha`.
+#[derive(Clone, Copy, PartialEq)]
+enum Shot {
+    Play(f32),   // lo explorado del jugador
+    Replay(f32), // un mapa aparte que arranca vacío con la repetición
+    Still,       // pausa: el mismo frame otra vez, no se marca nada
 }
 
-struct ScreamerState {
-    pos: Vector2,
-    active: bool,
-    timer: f32,
-    enabled: bool, // solo se activa si el mapa trae 'S'
+/// Vista 3D: se rasteriza en CPU (`fb`) y se sube como una sola textura por frame
+struct View {
+    fb: FrameBuffer,
+    tex: FrameTexture,
+    fov: f32,           // radianes, de las preferencias
+    map: MiniMapCfg,    // de las preferencias; el zoom se cambia también jugando
+    full_map: bool,     // mapa completo en vez del minimapa
+    explored: Explored, // lo que vio el jugador en el nivel activo
+    replay_fog: Explored, // lo que se va viendo en la repetición (no se guarda)
+    assist: bool,       // migas y pistas (de las preferencias)
+}
+
+impl View {
+    /// Del tamaño actual de la ventana
+    fn new(rl: &mut RaylibHandle, thread: &RaylibThread, settings: &Settings) -> Self {
+        let fb = FrameBuffer::new(rl.get_screen_width(), rl.get_screen_height());
+        let tex = FrameTexture::new(rl, thread, &fb);
+        let map = settings.minimap(rl.get_screen_height());
+        Self {
+            fb,
+            tex,
+            fov: settings.fov(),
+            map,
+            full_map: false,
+            explored: Explored::default(),
+            replay_fog: Explored::default(),
+            assist: settings.assist,
+        }
+    }
+
+    /// Mapa completo y zoom del minimapa (jugando y en la repetición)
+    fn map_keys(&mut self, rl: &RaylibHandle, controls: &Controls) {
+        if controls.pressed(rl, InputAction::Map) {
+            self.full_map = !self.full_map;
+        }
+        if controls.pressed(rl, InputAction::MapZoomIn) {
+            self.map.scale = minimap::zoom_step(self.map.scale, 1);
+        }
+        if controls.pressed(rl, InputAction::MapZoomOut) {
+            self.map.scale = minimap::zoom_step(self.map.scale, -1);
+        }
+    }
+
+    /// Migas y pista para el mapa; nada si las ayudas están apagadas
+    fn guide<'a>(&'a self, sim: &'a Sim) -> Guide<'a> {
+        if !self.assist {
+            return Guide::default();
+        }
+        let (path, path_alpha) = sim.hint.as_ref().map_or((&[][..], 0.0), |h| (h.path(), h.alpha(sim.time)));
+        Guide { crumbs: sim.trail.crumbs(), path, path_alpha }
+    }
+
+    /// Tras cambiar las opciones; lo explorado se conserva
+    fn rebuild(&mut self, rl: &mut RaylibHandle, thread: &RaylibThread, settings: &Settings) {
+        let explored = std::mem::take(&mut self.explored);
+        *self = Self { explored, ..Self::new(rl, thread, settings) };
+    }
+
+    /// Si cambió el nivel activo guarda lo explorado del anterior y carga lo del nuevo
+    fn sync_explored(&mut self, levels: &Levels) {
+        if self.explored.level() == Some(levels.source()) {
+            return;
+        }
+        save_explored(&self.explored);
+        self.explored = Explored::load(levels.source(), levels.active());
+    }
 }
 
 fn main() {
-    // ---------- ventana ----------
-    let (mut rl, thread) = raylib::init()
-        .size(1024, 640)
-        .title("Escape Reputation")
-        .build();
+    // modo sin ventana: `--render-png salida.png --level levels/01.txt ...`
+    let args: Vec<String> = std::env::args().skip(1).collect();
+
+    // `--verify-replay archivo.replay`: re-simula y compara con el final grabado
+    if let Some(i) = args.iter().position(|a| a == "--verify-replay") {
+        let Some(path) = args.get(i + 1) else {
+            eprintln!("Falta el valor de --verify-replay");
+            std::process::exit(1);
+        };
+        match Replay::load(path).and_then(|r| Ok((r.verify()?, r.hints))) {
+            Ok((end, hints)) => println!("{}: ok ({:?} en {}, {} pistas)", path, end.outcome, format_time(end.time), hints),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(parsed) = headless::HeadlessArgs::parse(&args) {
+        if let Err(e) = parsed.and_then(|a| headless::run(&a)) {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
 
-    rl.set_target_fps(120);
-    rl.set_exit_key(Some(KeyboardKey::KEY_ESCAPE));
+    // ---------- ventana ----------
+    // preferencias del usuario (settings.cfg en su carpeta de configuración)
+    let mut settings = Settings::load();
+    let mut builder = raylib::init();
+    builder.size(settings.width, settings.height).title("Escape Reputation");
+    if settings.vsync { builder.vsync(); }
+    if settings.fullscreen { builder.fullscreen(); }
+    let (mut rl, thread) = builder.build();
+
+    rl.set_target_fps(settings.fps_cap);
+    rl.set_exit_key(None); // ESC pausa en juego y sale desde el menú
     rl.disable_cursor(); // lock de mouse desde el inicio
 
     // ---------- niveles ----------
     let mut levels = Levels::load_from_dir("levels")
         .expect("No se pudieron cargar niveles desde ./levels. Crea la carpeta y pon mapas .txt");
-    assert!(levels.len() > 0, "No hay niveles en ./levels");
-
-    // Maze actual y punto de inicio (se asignan al arrancar nivel)
-    let mut player = Player::new(Vector2::new(64.0 * 1.5, 64.0 * 1.5));
+    assert!(!levels.is_empty(), "No hay niveles en ./levels");
 
-    // Screamer inicial
-    let mut screamer = ScreamerState {
-        pos: Vector2::zero(),
-        active: false,
-        timer: 0.0,
-        enabled: false,
-    };
+    // estado del nivel en juego (jugador, entidades, reloj); se rehace en start_level
+    // entrada de cada frame de la partida en curso; `replay_frame` avanza al mostrarla
+    let mut recording = Replay::new(levels.source().clone(), settings.screamer);
+    let mut sim = recording.new_sim(levels.active(), levels.meta());
+    let mut replay_frame: usize = 0;
+    // tiempo real → ticks fijos de la simulación (juego y repetición)
+    let mut clock = FixedStep::default();
 
     // ---------- assets visuales ----------
-    let wall_tex = rl.load_texture(&thread, "assets/wall.png")
-        .expect("Falta assets/wall.png");
-    let door_tex = rl.load_texture(&thread, "assets/door.png")
-        .expect("Falta assets/door.png");
+    // paredes/puerta/piso/techo/sprites se muestrean en CPU (framebuffer);
+    // las del nivel se recargan en start_level según su cabecera
+    let mut level_tex = LevelTextures::load(levels.meta())
+        .unwrap_or_else(|e| panic!("{}", e));
+    let sprite_sheet = SpriteSheet::load()
+        .unwrap_or_else(|e| panic!("{}", e));
 
     let menu_bg = load_bg_any(&mut rl, &thread, "menu_bg");
     let win_bg  = load_bg_any(&mut rl, &thread, "win_bg");
@@ -69,30 +202,49 @@ fn main() {
     let screamer_tex = rl.load_texture(&thread, "assets/screamer.png")
         .expect("Falta assets/screamer.png");
 
-    unsafe { ffi::InitAudioDevice(); }
-
-    // música en loop
-    let music_path = CString::new("sounds/music.ogg").expect("ruta music.ogg inválida");
-    let mut music: ffi::Music = unsafe { ffi::LoadMusicStream(music_path.as_ptr()) };
-    unsafe { ffi::SetMusicVolume(music, 0.60); }
-
-    // efectos
-    let step_path = CString::new("sounds/step.wav").expect("ruta step.wav inválida");
-    let step_snd: ffi::Sound = unsafe { ffi::LoadSound(step_path.as_ptr()) };
-    unsafe { ffi::SetSoundVolume(step_snd, 0.75); }
+    // la vista 3D se rasteriza en CPU y se sube como una sola textura por frame
+    let mut view = View::new(&mut rl, &thread, &settings);
 
-    let screamer_path = CString::new("sounds/screamer.wav").expect("ruta screamer.wav inválida");
-    let screamer_snd: ffi::Sound = unsafe { ffi::LoadSound(screamer_path.as_ptr()) };
+    // ---------- audio ----------
+    // sin dispositivo (o con --no-audio) todo sigue igual, sin sonido;
+    // la música de cada nivel (@music) arranca en start_level
+    let mut audio = if args.iter().any(|a| a == "--no-audio") { Audio::disabled() } else { Audio::init() };
+    apply_volumes(&mut audio, &settings);
+    let step_snd = audio.load_sound("step", "sounds/step.wav", Category::Sfx, 0.75);
+    let screamer_snd = audio.load_sound("screamer", "sounds/screamer.wav", Category::Sfx, 1.0);
+    let enemy_step_snd = audio.load_sound("enemy_step", "sounds/enemy_step.wav", Category::Sfx, 0.9);
+    let ui_snd = audio.load_sound("ui", "sounds/ui.wav", Category::Ui, 0.6);
 
-    // pasos por distancia
-    let mut step_accum: f32 = 0.0;  // píxeles acumulados caminados
-    const STEP_PIXELS: f32 = 34.0;  // distancia entre pasos (~medio tile si tile=64)
+    // susto a pantalla completa antes del cartel de game over
+    let mut scare_timer: f32 = 0.0;
 
     // ---------- estado de app ----------
     let mut state = AppState::Menu;
     let mut menu_sel: usize = 0;
 
-    while !rl.window_should_close() {
+    // mapa de acciones (controls.cfg en la carpeta de configuración del usuario)
+    let mut controls = Controls::load();
+    let mut controls_sel: usize = 0;
+    let mut rebinding: Option<f32> = None; // segundos que quedan esperando la tecla/botón nuevo
+    let mut controls_back = AppState::Menu; // a dónde vuelve la pantalla de controles
+    let mut settings_sel: usize = 0;
+    let mut settings_back = AppState::Menu; // a dónde vuelve la pantalla de opciones
+    let mut pause_sel: usize = 0;
+    let mut quit = false;
+    let mut apply_video = false; // resolución/pantalla completa se aplican fuera del dibujo
+
+    // laberinto aleatorio: la semilla se edita en el menú para poder compartirla
+    let mut seed_text = initial_seed().to_string();
+    let mut gen_algo: usize = 0;
+    let mut gen_size: usize = 1;
+
+    while !rl.window_should_close() && !quit {
+        if apply_video {
+            apply_video = false;
+            apply_video_settings(&mut rl, &settings);
+            view.rebuild(&mut rl, &thread, &settings);
+        }
+
         let mut d = rl.begin_drawing(&thread);
         let dt = d.get_frame_time();
         d.clear_background(Color::BLACK);
@@ -110,89 +262,164 @@ fn main() {
 
                 d.draw_rectangle(40, 40, sw as i32 - 80, sh as i32 - 80, Color::new(0, 0, 0, 120));
                 d.draw_text("SELECCIONA UN NIVEL", 70, 60, 28, Color::RAYWHITE);
-                d.draw_text("Usa flechitas y ENTER, o presiona 1..9", 70, 92, 18, Color::LIGHTGRAY);
+                d.draw_text("Usa flechitas y ENTER, o presiona 1..9   |   C: controles   |   O: opciones", 70, 92, 18, Color::LIGHTGRAY);
 
-                // números 1..9
-                for n in 1..=levels.len().min(9) {
-                    if let Some(key) = key_for_digit(n) {
-                        if d.is_key_pressed(key) {
-                            step_accum = 0.0;
-                            start_level(&mut levels, n - 1, &mut player, &mut screamer, &mut music, &mut state);
+                // la última entrada es el laberinto aleatorio
+                let entries = levels.len() + 1;
+                let on_random = menu_sel == levels.len();
+
+                if on_random {
+                    // campo de semilla: dígitos y BACKSPACE; izq/der algoritmo; TAB tamaño
+                    while let Some(ch) = d.get_char_pressed() {
+                        if ch.is_ascii_digit() && seed_text.len() < 18 { seed_text.push(ch); }
+                    }
+                    if d.is_key_pressed(KeyboardKey::KEY_BACKSPACE) { seed_text.pop(); }
+                    let n_algos = Algorithm::ALL.len();
+                    if d.is_key_pressed(KeyboardKey::KEY_RIGHT) { gen_algo = (gen_algo + 1) % n_algos; }
+                    if d.is_key_pressed(KeyboardKey::KEY_LEFT)  { gen_algo = (gen_algo + n_algos - 1) % n_algos; }
+                    if d.is_key_pressed(KeyboardKey::KEY_TAB)   { gen_size = (gen_size + 1) % GEN_SIZES.len(); }
+                } else {
+                    // números 1..9
+                    for n in 1..=levels.len().min(9) {
+                        if let Some(key) = key_for_digit(n) {
+                            if d.is_key_pressed(key) {
+                                (sim, recording) = start_level(&mut levels, n - 1, &mut clock, &mut level_tex, &mut audio, &settings);
+                                state = AppState::Playing;
+                            }
                         }
                     }
                 }
+                if d.is_key_pressed(KeyboardKey::KEY_C) {
+                    state = AppState::Controls;
+                    controls_sel = 0;
+                    rebinding = None;
+                    controls_back = AppState::Menu;
+                }
+                if d.is_key_pressed(KeyboardKey::KEY_O) {
+                    state = AppState::Settings;
+                    settings_sel = 0;
+                    settings_back = AppState::Menu;
+                }
+                if d.is_key_pressed(KeyboardKey::KEY_ESCAPE) {
+                    quit = true;
+                }
                 // flechas + Enter
-                if d.is_key_pressed(KeyboardKey::KEY_DOWN) { menu_sel = (menu_sel + 1) % levels.len(); }
-                if d.is_key_pressed(KeyboardKey::KEY_UP)   { menu_sel = (menu_sel + levels.len() - 1) % levels.len(); }
+                if d.is_key_pressed(KeyboardKey::KEY_DOWN) { menu_sel = (menu_sel + 1) % entries; audio.play(ui_snd); }
+                if d.is_key_pressed(KeyboardKey::KEY_UP)   { menu_sel = (menu_sel + entries - 1) % entries; audio.play(ui_snd); }
                 if d.is_key_pressed(KeyboardKey::KEY_ENTER) {
-                    step_accum = 0.0;
-                    start_level(&mut levels, menu_sel, &mut player, &mut screamer, &mut music, &mut state);
+                    let idx = if on_random {
+                        generate_level(&mut levels, &seed_text, Algorithm::ALL[gen_algo], gen_size)
+                    } else {
+                        menu_sel
+                    };
+                    (sim, recording) = start_level(&mut levels, idx, &mut clock, &mut level_tex, &mut audio, &settings);
+                    state = AppState::Playing;
                 }
 
                 // lista
                 let base_y = 140;
                 for i in 0..levels.len() {
                     let y = base_y + (i as i32) * 28;
-                    let name = levels.name(i);
-                    let line = format!("{} . {}", i + 1, name);
+                    let line = match levels.meta_at(i).and_then(|m| m.author.as_deref()) {
+                        Some(author) => format!("{} . {}  ({})", i + 1, levels.name(i), author),
+                        None => format!("{} . {}", i + 1, levels.name(i)),
+                    };
                     let color = if i == menu_sel { Color::YELLOW } else { Color::RAYWHITE };
                     d.draw_text(&line, 80, y, 22, color);
                 }
+
+                let y = base_y + (levels.len() as i32) * 28;
+                let line = format!(
+                    "? . Laberinto aleatorio   semilla: {}_   < {} >   {}",
+                    seed_text, Algorithm::ALL[gen_algo].name(), GEN_SIZES[gen_size].0
+                );
+                let color = if on_random { Color::YELLOW } else { Color::RAYWHITE };
+                d.draw_text(&line, 80, y, 22, color);
+                if on_random {
+                    d.draw_text("Escribe la semilla  |  izq/der: algoritmo  |  TAB: tamaño", 100, y + 28, 18, Color::LIGHTGRAY);
+                }
             }
 
             AppState::Playing => {
                 // actualizar música stream
-                unsafe { ffi::UpdateMusicStream(music); }
+                audio.update_music();
 
                 // ---- UPDATE ----
-                let prev_pos = player.pos;
-                player.update(&mut d, levels.active(), dt);
-
-                // pasos por distancia recorrida
-                let delta = (player.pos - prev_pos).length();
-                if delta > 0.0 {
-                    step_accum += delta;
-                    if step_accum >= STEP_PIXELS {
-                        // (opcional) variar ligeramente pitch
-                        // unsafe { ffi::SetSoundPitch(step_snd, 0.98 + ((rl.get_time() as f32).sin().abs() * 0.04)); }
-                        unsafe { ffi::PlaySound(step_snd); }
-                        step_accum -= STEP_PIXELS;
+                let mut input = InputSnapshot::read(&d, &controls);
+                input.hint_pressed &= view.assist; // sin ayudas no hay pistas (ni en la grabación)
+                for tick in clock.advance(dt, &input) {
+                    if state != AppState::Playing {
+                        break;
                     }
-                }
-
-                // Puerta (victoria) delante + E
-                if is_near_door_use(levels.active(), &player) && d.is_key_pressed(KeyboardKey::KEY_E) || (d.is_gamepad_available(0)
-                    && d.is_gamepad_button_pressed(0, GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN)) {
-                    unsafe {
-                        ffi::StopMusicStream(music);
-                        ffi::StopSound(step_snd);
+                    recording.push(TICK, tick);
+                    for ev in sim.step(levels.active(), &tick, TICK) {
+                        if state != AppState::Playing {
+                            break;
+                        }
+                        match ev {
+                            SimEvent::Footstep => {
+                                audio.play(step_snd);
+                            }
+                            SimEvent::EntityStep(idx) => {
+                                audio.play_at(enemy_step_snd, entity_sound(levels.active(), &sim, idx));
+                            }
+                            SimEvent::Won => {
+                                audio.stop_music();
+                                audio.stop(step_snd);
+                                save_recording(&mut recording, &sim, Outcome::Won);
+                                state = AppState::WonLevel;
+                            }
+                            SimEvent::Caught(idx) => {
+                                audio.stop_music();
+                                audio.stop(step_snd);
+                                audio.play_at(screamer_snd, entity_sound(levels.active(), &sim, idx));
+                                save_recording(&mut recording, &sim, Outcome::Caught);
+                                scare_timer = levels.meta().screamer.duration;
+                                state = AppState::GameOver;
+                            }
+                            SimEvent::Hint => recording.hints += 1,
+                            SimEvent::Ai(..) => {}
+                        }
                     }
-                    step_accum = 0.0;
-                    state = AppState::WonLevel;
                 }
 
-                // Screamer por proximidad + LOS
-                update_screamer(&mut d, levels.active(), &player, &mut screamer, &screamer_snd);
-
-                // ---- DRAW 3D ----
-                render_3d_scene(&mut d, levels.active(), &player, &wall_tex, &door_tex);
+                if state != AppState::Playing {
+                    clock.clear(); // ganó o lo atraparon: lo que quedó pendiente se descarta
+                }
 
-                // ---- UI / Minimapa ----
-                draw_minimap(
-                    &mut d, levels.active(), &player, None,
-                    MiniMapCfg { tile_px: 6, margin: 8, scale: 1.0 }
+                view.map_keys(&d, &controls);
+                let alpha = if state == AppState::Playing { clock.alpha() } else { 1.0 };
+                draw_level_view(&mut d, &mut view, &levels, &sim, Shot::Play(alpha), &level_tex, &sprite_sheet);
+                let mut footer = format!(
+                    "{}: usar puerta   |   {}: mapa   |   ESC o {}: pausa",
+                    action_hint(&controls, InputAction::Use), action_hint(&controls, InputAction::Map),
+                    action_hint(&controls, InputAction::Pause)
                 );
+                if view.assist {
+                    footer += &format!("   |   {}: pista", action_hint(&controls, InputAction::Hint));
+                }
+                d.draw_text(&footer, 10, d.get_screen_height() - 22, 16, Color::RAYWHITE);
+
+                // pausa: se congela todo (sim, música) y se suelta el mouse
+                if state == AppState::Playing
+                    && (d.is_key_pressed(KeyboardKey::KEY_ESCAPE) || controls.pressed(&d, InputAction::Pause))
+                {
+                    audio.pause_music();
+                    audio.stop(step_snd);
+                    clock.clear();
+                    d.enable_cursor();
+                    pause_sel = 0;
+                    state = AppState::Paused;
+                }
+            }
 
-                d.draw_text(
-                    &format!("Nivel {}/{}", levels.index() + 1, levels.len()),
-                    10, 10 + (levels.active().height() as i32 * 6) + 16,
-                    16, Color::RAYWHITE
-                );
-                d.draw_text("E o X en el mando: usar puerta/  |   ESC: salir", 10, d.get_screen_height() - 22, 16, Color::RAYWHITE);
+            AppState::GameOver => {
+                // el último frame de juego queda de fondo
+                view.tex.present(&mut d, &view.fb);
+                let (sw, sh) = (d.get_screen_width(), d.get_screen_height());
 
-                // Screamer overlay
-                if screamer.active {
-                    let (sw, sh) = (d.get_screen_width(), d.get_screen_height());
+                if scare_timer > 0.0 {
+                    scare_timer -= dt;
                     d.draw_rectangle(0, 0, sw, sh, Color::new(220, 20, 60, 120));
                     let tw = screamer_tex.width() as f32;
                     let th = screamer_tex.height() as f32;
@@ -210,6 +437,25 @@ fn main() {
                         0.0,
                         Color::WHITE
                     );
+                } else {
+                    d.draw_rectangle(0, 0, sw, sh, Color::new(0, 0, 0, 170));
+                    d.draw_text("TE ATRAPÓ", 70, 60, 36, Color::RED);
+                    d.draw_text(&format!("Aguantaste {}", format_time(sim.time)), 70, 104, 22, Color::RAYWHITE);
+                    let hint = format!(
+                        "{}: reintentar  |   {}: menú",
+                        action_hint(&controls, InputAction::Restart), action_hint(&controls, InputAction::Menu)
+                    );
+                    d.draw_text(&hint, 70, 136, 22, Color::RAYWHITE);
+
+                    if controls.pressed(&d, InputAction::Restart) {
+                        let idx = levels.index();
+                        (sim, recording) = start_level(&mut levels, idx, &mut clock, &mut level_tex, &mut audio, &settings);
+                        state = AppState::Playing;
+                    }
+                    if controls.pressed(&d, InputAction::Menu) {
+                        state = AppState::Menu;
+                        menu_sel = levels.index();
+                    }
                 }
             }
 
@@ -223,11 +469,25 @@ fn main() {
                     Vector2::zero(), 0.0, Color::WHITE
                 );
 
-                unsafe { ffi::StopSound(step_snd); } // por si algo quedó colgado
+                audio.stop(step_snd); // por si algo quedó colgado
 
                 d.draw_rectangle(40, 40, sw as i32 - 80, sh as i32 - 80, Color::new(0, 0, 0, 140));
                 d.draw_text("¡GANASTE!", 70, 60, 36, Color::LIME);
-                d.draw_text("Elige otro nivel con 1-5 |   M: menú", 70, 104, 22, Color::RAYWHITE);
+                let hint = format!(
+                    "Elige otro nivel con 1-{} |   {}: menú   |   {}: ver repetición",
+                    levels.len().min(9), action_hint(&controls, InputAction::Menu), action_hint(&controls, InputAction::Replay)
+                );
+                d.draw_text(&hint, 70, 104, 22, Color::RAYWHITE);
+
+                let time_line = match levels.meta().par_time {
+                    Some(par) if sim.time <= par => format!("Tiempo: {}  (par {}, ¡bajo par!)", format_time(sim.time), format_time(par)),
+                    Some(par) => format!("Tiempo: {}  (par {})", format_time(sim.time), format_time(par)),
+                    None => format!("Tiempo: {}", format_time(sim.time)),
+                };
+                d.draw_text(&time_line, 420, 66, 22, Color::RAYWHITE);
+                if recording.hints > 0 {
+                    d.draw_text(&format!("Pistas usadas: {}", recording.hints), 420, 92, 18, Color::LIGHTGRAY);
+                }
 
                 // lista de niveles
                 let base_y = 150;
@@ -241,32 +501,174 @@ fn main() {
                 for n in 1..=levels.len().min(9) {
                     if let Some(key) = key_for_digit(n) {
                         if d.is_key_pressed(key) {
-                            step_accum = 0.0;
-                            start_level(&mut levels, n - 1, &mut player, &mut screamer, &mut music, &mut state);
+                            (sim, recording) = start_level(&mut levels, n - 1, &mut clock, &mut level_tex, &mut audio, &settings);
+                            state = AppState::Playing;
                         }
                     }
                 }
                 // volver al menú
-                if d.is_key_pressed(KeyboardKey::KEY_M) {
+                if controls.pressed(&d, InputAction::Menu) {
                     state = AppState::Menu;
                     menu_sel = levels.index();
                 }
+                // ver la partida de nuevo (misma entrada, mismo resultado)
+                if controls.pressed(&d, InputAction::Replay) {
+                    sim = recording.new_sim(levels.active(), levels.meta());
+                    replay_frame = 0;
+                    view.replay_fog = Explored::unsaved(levels.active());
+                    state = AppState::Replay;
+                }
+            }
+
+            AppState::Replay => {
+                // mismo ritmo que el juego: un tick grabado por cada TICK de tiempo real
+                for _ in 0..clock.ticks(dt) {
+                    let Some(f) = recording.frames.get(replay_frame) else {
+                        state = AppState::WonLevel;
+                        break;
+                    };
+                    for ev in sim.step(levels.active(), &f.input, f.dt) {
+                        match ev {
+                            SimEvent::Footstep => audio.play(step_snd),
+                            SimEvent::EntityStep(idx) => {
+                                audio.play_at(enemy_step_snd, entity_sound(levels.active(), &sim, idx));
+                            }
+                            SimEvent::Won | SimEvent::Caught(_) => state = AppState::WonLevel,
+                            SimEvent::Hint | SimEvent::Ai(..) => {}
+                        }
+                    }
+                    replay_frame += 1;
+                    if state != AppState::Replay {
+                        break;
+                    }
+                }
+                if controls.pressed(&d, InputAction::Menu) || d.is_key_pressed(KeyboardKey::KEY_ENTER) {
+                    state = AppState::WonLevel;
+                }
+
+                view.map_keys(&d, &controls);
+                let shot = Shot::Replay(clock.alpha());
+                draw_level_view(&mut d, &mut view, &levels, &sim, shot, &level_tex, &sprite_sheet);
+                let footer = format!(
+                    "REPETICIÓN  {}/{}   |   ENTER o {}: volver",
+                    replay_frame, recording.frames.len(), action_hint(&controls, InputAction::Menu)
+                );
+                d.draw_text(&footer, 10, d.get_screen_height() - 22, 16, Color::YELLOW);
+            }
+
+            AppState::Controls => {
+                if controls_screen(&mut d, &mut controls, &mut controls_sel, &mut rebinding) {
+                    if let Err(e) = controls.save() {
+                        eprintln!("No se pudieron guardar los controles: {}", e);
+                    }
+                    state = controls_back;
+                }
+            }
+
+            AppState::Settings => {
+                if settings_screen(&mut d, &mut settings, &mut controls, &mut settings_sel) {
+                    for res in [settings.save(), controls.save()] {
+                        if let Err(e) = res {
+                            eprintln!("No se pudieron guardar las opciones: {}", e);
+                        }
+                    }
+                    d.set_target_fps(settings.fps_cap);
+                    apply_volumes(&mut audio, &settings);
+                    apply_video = true;
+                    state = settings_back;
+                } else if d.is_key_pressed(KeyboardKey::KEY_C) {
+                    // las opciones se guardan al salir de ellas, al volver de los controles
+                    controls_sel = 0;
+                    rebinding = None;
+                    controls_back = AppState::Settings;
+                    state = AppState::Controls;
+                }
+            }
+
+            AppState::Paused => {
+                // la escena se vuelve a dibujar (la resolución pudo cambiar en opciones)
+                draw_level_view(&mut d, &mut view, &levels, &sim, Shot::Still, &level_tex, &sprite_sheet);
+                let (sw, sh) = (d.get_screen_width(), d.get_screen_height());
+                d.draw_rectangle(0, 0, sw, sh, Color::new(0, 0, 0, 150));
+                d.draw_text("PAUSA", 70, 60, 36, Color::RAYWHITE);
+
+                // teclado o mando (D-Pad + A); ESC/pausa de nuevo continúa
+                let n = PAUSE_ITEMS.len();
+                let pad = |b| d.is_gamepad_button_pressed(0, b);
+                if d.is_key_pressed(KeyboardKey::KEY_DOWN) || pad(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_DOWN) {
+                    pause_sel = (pause_sel + 1) % n;
+                    audio.play(ui_snd);
+                }
+                if d.is_key_pressed(KeyboardKey::KEY_UP) || pad(GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_UP) {
+                    pause_sel = (pause_sel + n - 1) % n;
+                    audio.play(ui_snd);
+                }
+                let choice = if d.is_key_pressed(KeyboardKey::KEY_ESCAPE) || controls.pressed(&d, InputAction::Pause) {
+                    Some(PauseItem::Resume)
+                } else if d.is_key_pressed(KeyboardKey::KEY_ENTER) || pad(GamepadButton::GAMEPAD_BUTTON_RIGHT_FACE_DOWN) {
+                    Some(PAUSE_ITEMS[pause_sel].0)
+                } else {
+                    None
+                };
+
+                for (i, (_, label)) in PAUSE_ITEMS.iter().enumerate() {
+                    let color = if i == pause_sel { Color::YELLOW } else { Color::RAYWHITE };
+                    d.draw_text(label, 80, 120 + i as i32 * 32, 24, color);
+                }
+
+                match choice {
+                    Some(PauseItem::Resume) => {
+                        audio.resume_music();
+                        d.disable_cursor();
+                        state = AppState::Playing;
+                    }
+                    Some(PauseItem::Restart) => {
+                        let idx = levels.index();
+                        (sim, recording) = start_level(&mut levels, idx, &mut clock, &mut level_tex, &mut audio, &settings);
+                        d.disable_cursor();
+                        state = AppState::Playing;
+                    }
+                    Some(PauseItem::Settings) => {
+                        settings_sel = 0;
+                        settings_back = AppState::Paused;
+                        state = AppState::Settings;
+                    }
+                    Some(PauseItem::Controls) => {
+                        controls_sel = 0;
+                        rebinding = None;
+                        controls_back = AppState::Paused;
+                        state = AppState::Controls;
+                    }
+                    Some(PauseItem::ForgetMap) => {
+                        if let Err(e) = view.explored.reset() {
+                            eprintln!("No se pudo borrar lo explorado: {}", e);
+                        }
+                    }
+                    Some(PauseItem::LevelSelect) => {
+                        save_recording(&mut recording, &sim, Outcome::Quit);
+                        audio.stop_music();
+                        d.disable_cursor();
+                        menu_sel = levels.index();
+                        state = AppState::Menu;
+                    }
+                    Some(PauseItem::Quit) => {
+                        save_recording(&mut recording, &sim, Outcome::Quit);
+                        quit = true;
+                    }
+                    None => {}
+                }
             }
         }
         let sw = d.get_screen_width();
-        d.draw_fps(sw - 100, 10);
+        // con el minimapa arriba a la derecha, los FPS van debajo de él
+        let fps_y = if view.map.corner == Corner::TopRight { view.map.margin + view.map.size + 12 } else { 10 };
+        d.draw_fps(sw - 100, fps_y);
         let dt_ms = d.get_frame_time() * 1000.0;
-        d.draw_text(&format!("{:.1} ms", dt_ms), sw - 100, 30, 18, Color::LIGHTGRAY);
+        d.draw_text(&format!("{:.1} ms", dt_ms), sw - 100, fps_y + 20, 18, Color::LIGHTGRAY);
 
     }
-
-    // ---------- cerrar audio ----------
-    unsafe {
-        ffi::UnloadSound(step_snd);
-        ffi::UnloadSound(screamer_snd);
-        ffi::UnloadMusicStream(music);
-        ffi::CloseAudioDevice();
-    }
+    save_explored(&view.explored);
+    // `audio` descarga todo y cierra el dispositivo al salir de scope
 }
 
 // ---- helpers ----
@@ -287,115 +689,285 @@ fn load_bg_any(rl: &mut RaylibHandle, thread: &RaylibThread, base: &str) -> Text
         .expect(&format!("Falta assets/{}.png (o .jpg/.jpeg/.bmp)", base))
 }
 
-fn key_for_digit(n: usize) -> Option<KeyboardKey> {
-    use KeyboardKey::*;
-    Some(match n {
-        1 => KEY_ONE, 2 => KEY_TWO, 3 => KEY_THREE,
-        4 => KEY_FOUR, 5 => KEY_FIVE, 6 => KEY_SIX,
-        7 => KEY_SEVEN, 8 => KEY_EIGHT, 9 => KEY_NINE,
-        _ => return None,
-    })
+/// Semilla inicial del menú (cambia en cada arranque)
+fn initial_seed() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|t| t.as_secs() % 100_000)
+        .unwrap_or(1)
 }
 
-fn start_level(
-    levels: &mut Levels,
-    lvl_index: usize,
-    player: &mut Player,
-    screamer: &mut ScreamerState,
-    music: &mut ffi::Music,
-    state: &mut AppState,
-) {
-    levels.set_current(lvl_index);
-    let maze = levels.active();
+/// Genera el laberinto aleatorio, lo guarda en `levels` y retorna su índice
+fn generate_level(levels: &mut Levels, seed_text: &str, algorithm: Algorithm, size: usize) -> usize {
+    let seed: u64 = seed_text.parse().unwrap_or(0);
+    let (_, cols, rows) = GEN_SIZES[size];
+    levels
+        .set_generated(LevelSource::Random { seed, algorithm, cols, rows })
+        .expect("generar un laberinto no lee archivos")
+}
 
-    *player = spawn_player_from_maze(maze);
+/// Cierra la grabación con el estado final y la escribe en LAST_REPLAY
+fn save_recording(recording: &mut Replay, sim: &Sim, outcome: Outcome) {
+    recording.end = Some(Ending::of(sim, outcome));
+    if let Err(e) = recording.save(LAST_REPLAY) {
+        eprintln!("No se pudo guardar {}: {}", LAST_REPLAY, e);
+    }
+}
+
+fn save_explored(explored: &Explored) {
+    if let Err(e) = explored.save() {
+        eprintln!("No se pudo guardar lo explorado: {}", e);
+    }
+}
 
-    // screamer: solo si hay 'S'
-    if let Some(p) = find_screamer_pos(maze) {
-        screamer.pos = p;
-        screamer.enabled = true;
+/// Vista 3D + minimapa + línea de nivel/tiempo (juego, repetición y pausa)
+fn draw_level_view(
+    d: &mut RaylibDrawHandle,
+    view: &mut View,
+    levels: &Levels,
+    sim: &Sim,
+    shot: Shot,
+    level_tex: &LevelTextures,
+    sprite_sheet: &SpriteSheet,
+) {
+    // ---- DRAW 3D ---- (interpolado entre los dos últimos ticks)
+    view.sync_explored(levels);
+    let (alpha, marks) = match shot {
+        Shot::Play(alpha) => (alpha, Some(&mut view.explored)),
+        Shot::Replay(alpha) => (alpha, Some(&mut view.replay_fog)),
+        Shot::Still => (1.0, None),
+    };
+    let player = sim.player_view(alpha);
+    let mut sprites = sim.world.sprites(sprite_sheet, alpha);
+    if view.assist {
+        sprites.extend(sim.trail.sprites(sprite_sheet));
+    }
+    render_3d_scene(
+        &mut view.fb, levels.active(), &player, &level_tex.scene(), &sprites, view.fov, marks,
+    );
+    view.tex.present(d, &view.fb);
+    let explored = if matches!(shot, Shot::Replay(_)) { &view.replay_fog } else { &view.explored };
+
+    // ---- UI / Minimapa ----
+    let (mut hud_x, mut hud_y) = (10, 10);
+    let markers = sim.world.markers(alpha);
+    let guide = view.guide(sim);
+    if view.full_map {
+        draw_full_map(d, levels.active(), &player, explored, &markers, &guide, &view.map);
+        (hud_x, hud_y) = (140, 32); // al lado del título
     } else {
-        screamer.enabled = false;
+        let map = draw_minimap(d, levels.active(), &player, explored, &markers, &guide, &view.map);
+        if view.map.corner == Corner::TopLeft {
+            hud_y = (map.y + map.height) as i32 + 12;
+        }
     }
-    screamer.active = false;
-    screamer.timer = 0.0;
 
-    // arranca música del nivel
-    unsafe { ffi::PlayMusicStream(*music); }
+    let hud = if levels.is_generated() {
+        format!("{}   {}", levels.name(levels.index()), format_time(sim.time))
+    } else {
+        format!("Nivel {}/{}: {}   {}", levels.index() + 1, levels.len(),
+            levels.name(levels.index()), format_time(sim.time))
+    };
+    d.draw_text(&hud, hud_x, hud_y, 16, Color::RAYWHITE);
+}
 
-    *state = AppState::Playing;
+/// m:ss
+fn format_time(secs: f32) -> String {
+    let s = secs.max(0.0) as u32;
+    format!("{}:{:02}", s / 60, s % 60)
+}
+
+/// Lo que hay que apretar para una acción, para los carteles: la tecla o, si no tiene, el botón
+fn action_hint(controls: &Controls, action: InputAction) -> String {
+    let b = controls.binding(action);
+    match (b.key, b.button) {
+        (Some(k), _) => key_name(k),
+        (None, Some(btn)) => button_name(btn),
+        (None, None) => "-".to_string(),
+    }
 }
 
-fn spawn_player_from_maze(maze: &Maze) -> Player {
-    if let Some(cell) = maze.find_char('P') {
-        let p = maze.cell_center_world(cell);
-        Player::new(p)
+/// Pantalla de controles: una fila por acción y al final los ajustes de
+/// mouse/mando. Se navega con teclas fijas. Retorna true al salir (BACKSPACE).
+/// `rebinding` = segundos que le quedan a la reasignación en curso.
+fn controls_screen(d: &mut RaylibDrawHandle, controls: &mut Controls, sel: &mut usize, rebinding: &mut Option<f32>) -> bool {
+    let actions = InputAction::ALL;
+    let rows = actions.len() + 4;
+
+    if let Some(left) = rebinding {
+        // la primera tecla o botón que llegue reemplaza al de ese dispositivo;
+        // ESC no se puede asignar: cancela, igual que dejar pasar el tiempo
+        *left -= d.get_frame_time();
+        let action = actions[*sel];
+        let mut b = controls.binding(action);
+        if d.is_key_pressed(KeyboardKey::KEY_ESCAPE) || *left <= 0.0 {
+            *rebinding = None;
+        } else if let Some(k) = d.get_key_pressed() {
+            b.key = Some(k);
+            *rebinding = None;
+        } else if let Some(btn) = d.get_gamepad_button_pressed() {
+            b.button = Some(btn);
+            *rebinding = None;
+        }
+        controls.set_binding(action, b);
     } else {
-        let bs = maze.block_size() as f32;
-        Player::new(Vector2::new(bs * 1.5, bs * 1.5))
+        if d.is_key_pressed(KeyboardKey::KEY_DOWN) { *sel = (*sel + 1) % rows; }
+        if d.is_key_pressed(KeyboardKey::KEY_UP)   { *sel = (*sel + rows - 1) % rows; }
+
+        let step = if d.is_key_pressed(KeyboardKey::KEY_RIGHT) {
+            1.0
+        } else if d.is_key_pressed(KeyboardKey::KEY_LEFT) {
+            -1.0
+        } else {
+            0.0
+        };
+        match sel.checked_sub(actions.len()) {
+            None => {
+                if d.is_key_pressed(KeyboardKey::KEY_ENTER) { *rebinding = Some(REBIND_SECONDS); }
+                if d.is_key_pressed(KeyboardKey::KEY_DELETE) { controls.set_binding(actions[*sel], Binding::default()); }
+            }
+            Some(0) => controls.mouse_sensitivity = (controls.mouse_sensitivity + step * 0.0004).clamp(0.0004, 0.02),
+            Some(1) => controls.deadzone = (controls.deadzone + step * 0.02).clamp(0.0, 0.6),
+            Some(2) if step != 0.0 => controls.invert_turn = !controls.invert_turn,
+            Some(3) if step != 0.0 => controls.invert_move = !controls.invert_move,
+            _ => {}
+        }
+        if d.is_key_pressed(KeyboardKey::KEY_F5) { *controls = Controls::default(); }
+    }
+
+    // ---- dibujo ----
+    let (sw, sh) = (d.get_screen_width(), d.get_screen_height());
+    d.draw_rectangle(40, 40, sw - 80, sh - 80, Color::new(0, 0, 0, 170));
+    d.draw_text("CONTROLES", 70, 60, 28, Color::RAYWHITE);
+    let help = match *rebinding {
+        Some(left) => format!("Presiona la tecla o el botón del mando nuevo...  (ESC: cancelar, {:.0} s)", left.ceil()),
+        None => "ENTER: cambiar  |  SUPR: borrar  |  izq/der: ajustar  |  F5: por defecto  |  BACKSPACE: guardar y volver".to_string(),
+    };
+    d.draw_text(&help, 70, 92, 18, Color::LIGHTGRAY);
+
+    let yes_no = |v: bool| if v { "sí" } else { "no" };
+    let settings = [
+        ("Sensibilidad del mouse", format!("{:.4}", controls.mouse_sensitivity)),
+        ("Zona muerta de los sticks", format!("{:.2}", controls.deadzone)),
+        ("Invertir giro", yes_no(controls.invert_turn).to_string()),
+        ("Invertir stick de movimiento", yes_no(controls.invert_move).to_string()),
+    ];
+    let lines = actions
+        .iter()
+        .map(|&a| (a.label(), binding_text(controls.binding(a))))
+        .chain(settings);
+    let row_h = list_row_height(sh, rows, 26);
+    for (i, (label, value)) in lines.enumerate() {
+        let y = 130 + i as i32 * row_h;
+        let color = match (i == *sel, rebinding.is_some()) {
+            (true, true) => Color::ORANGE,
+            (true, false) => Color::YELLOW,
+            _ => Color::RAYWHITE,
+        };
+        d.draw_text(label, 80, y, row_h - 6, color);
+        d.draw_text(&value, 420, y, row_h - 6, color);
     }
+
+    rebinding.is_none() && d.is_key_pressed(KeyboardKey::KEY_BACKSPACE)
 }
 
-fn find_screamer_pos(maze: &Maze) -> Option<Vector2> {
-    maze.find_char('S').map(|c| maze.cell_center_world(c))
+/// Cómo oye el jugador un sonido que sale de la entidad `idx`
+fn entity_sound(maze: &Maze, sim: &Sim, idx: usize) -> Spatial {
+    Spatial::at(maze, sim.player.pos, sim.player.a, sim.world.entities[idx].pos)
 }
 
-fn is_near_door_use(maze: &Maze, player: &Player) -> bool {
-    let bs = maze.block_size() as f32;
-    let front = Vector2 {
-        x: player.pos.x + player.a.cos() * bs * 0.5,
-        y: player.pos.y + player.a.sin() * bs * 0.5,
-    };
-    let (i, j) = ((front.x / bs) as isize, (front.y / bs) as isize);
-    maze.is_door_at(i, j)
+/// Volúmenes por categoría desde las preferencias
+fn apply_volumes(audio: &mut Audio, settings: &Settings) {
+    audio.set_volume(Category::Music, settings.music_volume);
+    audio.set_volume(Category::Sfx, settings.sfx_volume);
+    audio.set_volume(Category::Ui, settings.ui_volume);
 }
 
-fn update_screamer(
-    d: &mut RaylibDrawHandle,
-    maze: &Maze,
-    player: &Player,
-    screamer: &mut ScreamerState,
-    snd: &ffi::Sound,
-) {
-    if !screamer.enabled {
-        return; // deshabilitado si el mapa no tiene 'S'
+/// Resolución y pantalla completa (vsync va como estado de ventana)
+fn apply_video_settings(rl: &mut RaylibHandle, settings: &Settings) {
+    if rl.is_window_fullscreen() != settings.fullscreen {
+        rl.toggle_fullscreen();
     }
+    if !settings.fullscreen {
+        rl.set_window_size(settings.width, settings.height);
+    }
+    let vsync = WindowState::default().set_vsync_hint(true);
+    if settings.vsync {
+        rl.set_window_state(vsync);
+    } else {
+        rl.clear_window_state(vsync);
+    }
+}
 
-    if screamer.active {
-        screamer.timer -= d.get_frame_time();
-        if screamer.timer <= 0.0 {
-            screamer.active = false;
-            screamer.timer = 0.0;
-        }
-        return;
+/// Pantalla de opciones. Retorna true al salir (BACKSPACE); guardar y aplicar queda para quien llama.
+fn settings_screen(d: &mut RaylibDrawHandle, settings: &mut Settings, controls: &mut Controls, sel: &mut usize) -> bool {
+    let rows = SettingRow::ALL;
+    if d.is_key_pressed(KeyboardKey::KEY_DOWN) { *sel = (*sel + 1) % rows.len(); }
+    if d.is_key_pressed(KeyboardKey::KEY_UP)   { *sel = (*sel + rows.len() - 1) % rows.len(); }
+    if d.is_key_pressed(KeyboardKey::KEY_RIGHT) { settings.adjust(rows[*sel], 1, controls); }
+    if d.is_key_pressed(KeyboardKey::KEY_LEFT)  { settings.adjust(rows[*sel], -1, controls); }
+    if d.is_key_pressed(KeyboardKey::KEY_F5) {
+        *settings = Settings::default();
+        controls.mouse_sensitivity = Controls::default().mouse_sensitivity;
     }
 
-    // proximidad + LOS
-    let trigger_dist = maze.block_size() as f32 * 1.2;
-    let dist = (player.pos - screamer.pos).length();
-    if dist <= trigger_dist && has_los(maze, player.pos, screamer.pos) {
-        screamer.active = true;
-        screamer.timer = 1.2;
-        unsafe { ffi::PlaySound(*snd); }
+    let (sw, sh) = (d.get_screen_width(), d.get_screen_height());
+    d.draw_rectangle(40, 40, sw - 80, sh - 80, Color::new(0, 0, 0, 170));
+    d.draw_text("OPCIONES", 70, 60, 28, Color::RAYWHITE);
+    d.draw_text("izq/der: cambiar  |  F5: por defecto  |  C: controles  |  BACKSPACE: guardar y volver", 70, 92, 18, Color::LIGHTGRAY);
+    let row_h = list_row_height(sh, rows.len(), 28);
+    for (i, &row) in rows.iter().enumerate() {
+        let y = 130 + i as i32 * row_h;
+        let color = if i == *sel { Color::YELLOW } else { Color::RAYWHITE };
+        d.draw_text(row.label(), 80, y, row_h - 6, color);
+        d.draw_text(&settings.value_text(row, controls), 420, y, row_h - 6, color);
     }
+    d.draw_text("La resolución y la pantalla completa se aplican al volver", 70, sh - 70, 18, Color::LIGHTGRAY);
+
+    d.is_key_pressed(KeyboardKey::KEY_BACKSPACE)
+}
+
+/// Alto de fila de las listas de opciones/controles (desde y = 130) para que
+/// entren todas sobre el pie de la pantalla; nunca más que `max`
+fn list_row_height(screen_h: i32, rows: usize, max: i32) -> i32 {
+    ((screen_h - 210) / rows as i32).clamp(16, max)
+}
+
+fn key_for_digit(n: usize) -> Option<KeyboardKey> {
+    use KeyboardKey::*;
+    Some(match n {
+        1 => KEY_ONE, 2 => KEY_TWO, 3 => KEY_THREE,
+        4 => KEY_FOUR, 5 => KEY_FIVE, 6 => KEY_SIX,
+        7 => KEY_SEVEN, 8 => KEY_EIGHT, 9 => KEY_NINE,
+        _ => return None,
+    })
 }
 
-// Línea de vista simple
-fn has_los(maze: &Maze, a: Vector2, b: Vector2) -> bool {
-    let bs = maze.block_size() as f32;
-    let dx = b.x - a.x;
-    let dy = b.y - a.y;
-    let steps = (dx.abs().max(dy.abs()) / (bs * 0.5)).max(1.0);
-    let sx = dx / steps;
-    let sy = dy / steps;
-
-    let mut x = a.x;
-    let mut y = a.y;
-    for _ in 0..steps as i32 {
-        let i = (x / bs) as isize;
-        let j = (y / bs) as isize;
-        if maze.is_blocking_at(i, j) { return false; }
-        x += sx; y += sy;
+/// Carga el nivel y arranca la música; retorna la simulación y la grabación nuevas
+fn start_level(
+    levels: &mut Levels,
+    lvl_index: usize,
+    clock: &mut FixedStep,
+    level_tex: &mut LevelTextures,
+    audio: &mut Audio,
+    settings: &Settings,
+) -> (Sim, Replay) {
+    levels.set_current(lvl_index);
+    let meta = levels.meta();
+    match LevelTextures::load(meta) {
+        Ok(t) => *level_tex = t,
+        Err(e) => eprintln!("{}", e), // se quedan las texturas anteriores
     }
-    true
+    let maze = levels.active();
+
+    // jugador en la 'P' y una entidad por cada 'S', 'D', ...
+    // (los screamers solo si la cabecera y las opciones no los apagan)
+    let recording = Replay::new(levels.source().clone(), settings.screamer);
+    let sim = recording.new_sim(maze, meta);
+    // nada de la partida anterior (un "usar" a medio tick) pasa a la nueva
+    clock.clear();
+
+    // arranca música del nivel
+    audio.play_music(&meta.music);
+    (sim, recording)
 }
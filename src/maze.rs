@@ -1,34 +1,57 @@
 use raylib::prelude::*;
+use std::cell::RefCell;
+use crate::tile::Tile;
+
+mod boxdraw;
+pub mod generate;
+mod path;
+
+pub use boxdraw::is_box_drawing;
+pub use path::{Cell, Connectivity, DistanceField};
 
 #[derive(Clone)]
 pub struct Maze {
-    grid: Vec<Vec<char>>,
+    grid: Vec<Vec<Tile>>,
     block_size: u32,
     w: usize,
     h: usize,
+    fields: RefCell<path::FieldCache>, // campos de distancia ya calculados
 }
 
 impl Maze {
-    pub fn new(grid: Vec<Vec<char>>, block_size: u32) -> Self {
+    pub fn new(grid: Vec<Vec<Tile>>, block_size: u32) -> Self {
         let h = grid.len();
         let w = if h > 0 { grid[0].len() } else { 0 };
-        Self { grid, block_size, w, h }
+        Self { grid, block_size, w, h, fields: RefCell::default() }
+    }
+
+    /// Crea un Maze detectando el formato: grid de '#' o laberinto "+--+"
+    pub fn parse(text: &str, block_size: u32) -> Self {
+        if boxdraw::is_box_drawing(text) {
+            Self::from_box_drawing(text, block_size)
+        } else {
+            Self::from_str_map(text, block_size)
+        }
     }
 
-    /// Crea un Maze a partir del texto de un .txt (múltiples líneas)
+    /// Crea un Maze a partir del texto de un .txt (múltiples líneas).
+    /// Los caracteres desconocidos se toman como piso vacío.
     pub fn from_str_map(text: &str, block_size: u32) -> Self {
-        let mut rows: Vec<Vec<char>> = Vec::new();
+        let mut rows: Vec<Vec<Tile>> = Vec::new();
         for line in text.lines() {
-            let row: Vec<char> = line.chars().collect();
+            let row: Vec<Tile> = line
+                .chars()
+                .map(|c| Tile::from_char(c).unwrap_or(Tile::Empty))
+                .collect();
             if !row.is_empty() {
                 rows.push(row);
             }
         }
-        // Normalizar anchura con padding de espacios
+        // Normalizar anchura con padding de piso vacío
         let max_w = rows.iter().map(|r| r.len()).max().unwrap_or(0);
         for r in &mut rows {
             if r.len() < max_w {
-                r.extend(std::iter::repeat(' ').take(max_w - r.len()));
+                r.resize(max_w, Tile::Empty);
             }
         }
         Self::new(rows, block_size)
@@ -38,34 +61,36 @@ impl Maze {
     pub fn height(&self) -> usize { self.h }
     pub fn block_size(&self) -> u32 { self.block_size }
 
-    pub fn cell(&self, i: isize, j: isize) -> char {
-        if i < 0 || j < 0 { return '#'; }
+    /// Fuera del mapa todo es pared
+    pub fn cell(&self, i: isize, j: isize) -> Tile {
+        if i < 0 || j < 0 { return Tile::Wall; }
         let (i, j) = (i as usize, j as usize);
-        if j >= self.h || i >= self.w { return '#'; }
+        if j >= self.h || i >= self.w { return Tile::Wall; }
         self.grid[j][i]
     }
 
-    pub fn cell_i32(&self, i: i32, j: i32) -> char {
+    pub fn cell_i32(&self, i: i32, j: i32) -> Tile {
         self.cell(i as isize, j as isize)
     }
 
     pub fn is_blocking_at(&self, i: isize, j: isize) -> bool {
-        match self.cell(i, j) {
-            '#' => true,
-            'D' => true, // la puerta bloquea hasta que el jugador la “use”
-            _ => false,
-        }
+        // la puerta también es sólida: bloquea hasta que el jugador la “use”
+        self.cell(i, j).def().solid
+    }
+
+    pub fn has_ceiling(&self, i: isize, j: isize) -> bool {
+        self.cell(i, j).def().ceiling
     }
 
     pub fn is_door_at(&self, i: isize, j: isize) -> bool {
-        self.cell(i, j) == 'D'
+        self.cell(i, j).def().exit
     }
 
-    /// Busca la primera ocurrencia de un char y devuelve (i,j) en celdas
-    pub fn find_char(&self, ch: char) -> Option<(isize, isize)> {
+    /// Busca la primera celda que cumpla `pred` y devuelve (i,j) en celdas
+    pub fn find(&self, pred: impl Fn(Tile) -> bool) -> Option<(isize, isize)> {
         for j in 0..self.h {
             for i in 0..self.w {
-                if self.grid[j][i] == ch {
+                if pred(self.grid[j][i]) {
                     return Some((i as isize, j as isize));
                 }
             }
@@ -0,0 +1,132 @@
+use super::Maze;
+use crate::tile::Tile;
+
+// Formato "box-drawing" de los generadores ASCII típicos:
+//
+//   +--+--+
+//   |     |
+//   +  +--+
+//   |    g|
+//   +--+--+
+//
+// Las líneas que empiezan con '+' son bordes horizontales y las columnas de
+// los '+' de la primera línea son los bordes verticales. Cada celda del
+// laberinto pasa a ser un bloque en (2c+1, 2r+1); las esquinas y las paredes
+// entre celdas ocupan los bloques pares.
+
+/// true si el texto parece un laberinto "+--+" y no un grid de '#'
+pub fn is_box_drawing(text: &str) -> bool {
+    let Some(first) = text.lines().find(|l| !l.trim().is_empty()) else { return false; };
+    let first = first.trim_end();
+    first.starts_with('+') && first.contains('-') && first.chars().all(|c| c == '+' || c == '-')
+}
+
+impl Maze {
+    /// Convierte el formato box-drawing a un grid de bloques.
+    /// Marcadores dentro de una celda: `g`/`G`/`D` = puerta (meta), `P` = inicio, `S` = screamer,
+    /// `I` = objeto.
+    /// Sin `P`, el jugador empieza en la celda de arriba a la izquierda.
+    pub fn from_box_drawing(text: &str, block_size: u32) -> Self {
+        let lines: Vec<Vec<char>> = text
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(|l| l.trim_end().chars().collect())
+            .collect();
+        let at = |row: usize, col: usize| -> char {
+            lines.get(row).and_then(|l| l.get(col)).copied().unwrap_or(' ')
+        };
+
+        // bordes: filas que empiezan con '+' y columnas de '+' en la primera
+        let h_edges: Vec<usize> = (0..lines.len()).filter(|&r| at(r, 0) == '+').collect();
+        let v_edges: Vec<usize> = match lines.first() {
+            Some(l) => (0..l.len()).filter(|&c| l[c] == '+').collect(),
+            None => Vec::new(),
+        };
+        if h_edges.len() < 2 || v_edges.len() < 2 {
+            return Self::new(Vec::new(), block_size);
+        }
+        let (rows, cols) = (h_edges.len() - 1, v_edges.len() - 1);
+
+        let mut grid = vec![vec![Tile::Wall; 2 * cols + 1]; 2 * rows + 1];
+        let mut has_spawn = false;
+
+        for r in 0..rows {
+            let (top, bottom) = (h_edges[r], h_edges[r + 1]);
+            for c in 0..cols {
+                let (left, right) = (v_edges[c], v_edges[c + 1]);
+
+                // interior de la celda + marcador
+                let mut tile = Tile::Empty;
+                for row in top + 1..bottom {
+                    for col in left + 1..right {
+                        match at(row, col) {
+                            'g' | 'G' | 'D' => tile = Tile::Door,
+                            'P' => tile = Tile::Spawn,
+                            'S' => tile = Tile::Screamer,
+                            'I' => tile = Tile::Item,
+                            _ => {}
+                        }
+                    }
+                }
+                has_spawn |= tile == Tile::Spawn;
+                grid[2 * r + 1][2 * c + 1] = tile;
+
+                // pared a la derecha: '|' en el borde vertical (se mira la 1a fila interior)
+                if c + 1 < cols && at(top + 1, right) != '|' {
+                    grid[2 * r + 1][2 * c + 2] = Tile::Empty;
+                }
+                // pared abajo: '-' en el borde horizontal
+                if r + 1 < rows && at(bottom, left + 1) != '-' {
+                    grid[2 * r + 2][2 * c + 1] = Tile::Empty;
+                }
+            }
+        }
+
+        if !has_spawn {
+            // primera celda libre en orden de lectura
+            if let Some(cell) = (0..rows)
+                .flat_map(|r| (0..cols).map(move |c| (2 * r + 1, 2 * c + 1)))
+                .find(|&(j, i)| grid[j][i] == Tile::Empty)
+            {
+                grid[cell.0][cell.1] = Tile::Spawn;
+            }
+        }
+
+        Self::new(grid, block_size)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIXTURE: &str = "\
++--+--+--+
+|P    |  |
++--+  +  +
+|S     g |
++--+--+--+";
+
+    #[test]
+    fn detects_the_format() {
+        assert!(is_box_drawing(FIXTURE));
+        assert!(is_box_drawing(&format!("\n\n{}", FIXTURE)));
+        assert!(!is_box_drawing("#####\n#P D#\n#####"));
+        assert!(!is_box_drawing("+++\n|P|"));
+        assert!(!is_box_drawing(""));
+    }
+
+    #[test]
+    fn cells_walls_and_markers() {
+        let maze = Maze::from_box_drawing(FIXTURE, 64);
+        let expected = Maze::parse("#######\n#P  # #\n### # #\n#S   D#\n#######", 64);
+        assert_eq!(maze.grid, expected.grid);
+    }
+
+    #[test]
+    fn without_p_the_first_free_cell_is_the_spawn() {
+        let maze = Maze::from_box_drawing("+--+--+\n|S    |\n+--+--+", 64);
+        assert_eq!(maze.cell(1, 1), Tile::Screamer);
+        assert_eq!(maze.cell(3, 1), Tile::Spawn);
+    }
+}
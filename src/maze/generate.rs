@@ -0,0 +1,324 @@
+use std::collections::VecDeque;
+
+use super::Maze;
+use crate::tile::Tile;
+
+// Generación procedural de laberintos "perfectos" (un solo camino entre dos
+// celdas cualesquiera). Se trabaja sobre una grilla de celdas `cols x rows`
+// que luego se expande a bloques: la celda (c, r) es el bloque (2c+1, 2r+1) y
+// los bloques pares entre celdas son paredes que el algoritmo va abriendo.
+// Todo sale de la semilla, así que la misma semilla da el mismo mapa.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Algorithm {
+    Backtracker,
+    Prim,
+    Kruskal,
+    Wilson,
+}
+
+impl Algorithm {
+    pub const ALL: [Algorithm; 4] = [
+        Algorithm::Backtracker,
+        Algorithm::Prim,
+        Algorithm::Kruskal,
+        Algorithm::Wilson,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Algorithm::Backtracker => "backtracker",
+            Algorithm::Prim => "prim",
+            Algorithm::Kruskal => "kruskal",
+            Algorithm::Wilson => "wilson",
+        }
+    }
+}
+
+pub struct GenOptions {
+    pub cols: usize, // en celdas (el mapa queda de 2*cols+1 bloques)
+    pub rows: usize,
+    pub algorithm: Algorithm,
+    pub screamer: bool,
+}
+
+/// PRNG chico y determinista (SplitMix64); no depende de la plataforma
+#[derive(Clone)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Entero en [0, n)
+    pub fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n.max(1) as u64) as usize
+    }
+
+    pub fn shuffle<T>(&mut self, v: &mut [T]) {
+        for i in (1..v.len()).rev() {
+            v.swap(i, self.below(i + 1));
+        }
+    }
+}
+
+/// Construye un laberinto resoluble con 'P', 'D' (en la celda más lejana) y 'S' opcional
+pub fn generate(seed: u64, opts: &GenOptions, block_size: u32) -> Maze {
+    let (cols, rows) = (opts.cols.max(2), opts.rows.max(2));
+    let mut rng = Rng::new(seed);
+    let mut carver = Carver::new(cols, rows);
+
+    match opts.algorithm {
+        Algorithm::Backtracker => backtracker(&mut carver, &mut rng),
+        Algorithm::Prim => prim(&mut carver, &mut rng),
+        Algorithm::Kruskal => kruskal(&mut carver, &mut rng),
+        Algorithm::Wilson => wilson(&mut carver, &mut rng),
+    }
+
+    // inicio al azar; la puerta en la celda más lejana (en un laberinto
+    // perfecto es un callejón sin salida, así que se abre desde un solo lado)
+    let start = rng.below(cols * rows);
+    let (dist, parent) = carver.bfs(start);
+    let door = (0..cols * rows).max_by_key(|&c| (dist[c], c)).unwrap_or(start);
+
+    let mut grid = carver.grid;
+    let block = |c: usize| (2 * (c / cols) + 1, 2 * (c % cols) + 1);
+    let (sj, si) = block(start);
+    grid[sj][si] = Tile::Spawn;
+    let (dj, di) = block(door);
+    grid[dj][di] = Tile::Door;
+
+    // screamer a mitad del camino de la solución: el jugador tiene que pasar
+    if opts.screamer {
+        let mut path = vec![door];
+        let mut cur = door;
+        while parent[cur] != usize::MAX {
+            cur = parent[cur];
+            path.push(cur);
+        }
+        if path.len() >= 4 {
+            let (j, i) = block(path[path.len() / 2]);
+            grid[j][i] = Tile::Screamer;
+        }
+    }
+
+    Maze::new(grid, block_size)
+}
+
+// Grilla de bloques + conectividad entre celdas
+struct Carver {
+    cols: usize,
+    rows: usize,
+    grid: Vec<Vec<Tile>>,
+}
+
+impl Carver {
+    fn new(cols: usize, rows: usize) -> Self {
+        let mut grid = vec![vec![Tile::Wall; 2 * cols + 1]; 2 * rows + 1];
+        for r in 0..rows {
+            for c in 0..cols {
+                grid[2 * r + 1][2 * c + 1] = Tile::Empty;
+            }
+        }
+        Self { cols, rows, grid }
+    }
+
+    fn neighbors(&self, cell: usize) -> Vec<usize> {
+        let (r, c) = (cell / self.cols, cell % self.cols);
+        let mut out = Vec::with_capacity(4);
+        if r > 0 { out.push(cell - self.cols); }
+        if r + 1 < self.rows { out.push(cell + self.cols); }
+        if c > 0 { out.push(cell - 1); }
+        if c + 1 < self.cols { out.push(cell + 1); }
+        out
+    }
+
+    /// Bloque de pared entre dos celdas vecinas
+    fn wall_between(&self, a: usize, b: usize) -> (usize, usize) {
+        let (ar, ac) = (a / self.cols, a % self.cols);
+        let (br, bc) = (b / self.cols, b % self.cols);
+        (ar + br + 1, ac + bc + 1)
+    }
+
+    fn carve(&mut self, a: usize, b: usize) {
+        let (j, i) = self.wall_between(a, b);
+        self.grid[j][i] = Tile::Empty;
+    }
+
+    fn is_open(&self, a: usize, b: usize) -> bool {
+        let (j, i) = self.wall_between(a, b);
+        self.grid[j][i] != Tile::Wall
+    }
+
+    /// Distancias (en celdas) y padres desde `start`; usize::MAX = sin padre
+    fn bfs(&self, start: usize) -> (Vec<usize>, Vec<usize>) {
+        let n = self.cols * self.rows;
+        let mut dist = vec![usize::MAX; n];
+        let mut parent = vec![usize::MAX; n];
+        let mut queue = VecDeque::from([start]);
+        dist[start] = 0;
+        while let Some(cur) = queue.pop_front() {
+            for nb in self.neighbors(cur) {
+                if dist[nb] == usize::MAX && self.is_open(cur, nb) {
+                    dist[nb] = dist[cur] + 1;
+                    parent[nb] = cur;
+                    queue.push_back(nb);
+                }
+            }
+        }
+        // inalcanzables (no debería pasar) quedan fuera del max_by_key
+        for d in &mut dist {
+            if *d == usize::MAX { *d = 0; }
+        }
+        (dist, parent)
+    }
+}
+
+// DFS con pila: pasillos largos y pocas bifurcaciones
+fn backtracker(m: &mut Carver, rng: &mut Rng) {
+    let n = m.cols * m.rows;
+    let mut visited = vec![false; n];
+    let start = rng.below(n);
+    let mut stack = vec![start];
+    visited[start] = true;
+
+    while let Some(&cur) = stack.last() {
+        let options: Vec<usize> = m.neighbors(cur).into_iter().filter(|&nb| !visited[nb]).collect();
+        if options.is_empty() {
+            stack.pop();
+            continue;
+        }
+        let next = options[rng.below(options.len())];
+        m.carve(cur, next);
+        visited[next] = true;
+        stack.push(next);
+    }
+}
+
+// Prim aleatorio: crece desde una celda tomando paredes de la frontera al azar
+fn prim(m: &mut Carver, rng: &mut Rng) {
+    let n = m.cols * m.rows;
+    let mut inside = vec![false; n];
+    let start = rng.below(n);
+    inside[start] = true;
+    let mut frontier: Vec<(usize, usize)> = m.neighbors(start).into_iter().map(|nb| (start, nb)).collect();
+
+    while !frontier.is_empty() {
+        let (from, to) = frontier.swap_remove(rng.below(frontier.len()));
+        if inside[to] {
+            continue;
+        }
+        m.carve(from, to);
+        inside[to] = true;
+        for nb in m.neighbors(to) {
+            if !inside[nb] {
+                frontier.push((to, nb));
+            }
+        }
+    }
+}
+
+// Kruskal: todas las paredes en orden aleatorio, se abre si une dos conjuntos distintos
+fn kruskal(m: &mut Carver, rng: &mut Rng) {
+    let n = m.cols * m.rows;
+    let mut edges = Vec::new();
+    for cell in 0..n {
+        if cell % m.cols + 1 < m.cols { edges.push((cell, cell + 1)); }
+        if cell / m.cols + 1 < m.rows { edges.push((cell, cell + m.cols)); }
+    }
+    rng.shuffle(&mut edges);
+
+    let mut parent: Vec<usize> = (0..n).collect();
+    fn root(parent: &mut [usize], mut x: usize) -> usize {
+        while parent[x] != x {
+            parent[x] = parent[parent[x]];
+            x = parent[x];
+        }
+        x
+    }
+
+    for (a, b) in edges {
+        let (ra, rb) = (root(&mut parent, a), root(&mut parent, b));
+        if ra != rb {
+            parent[ra] = rb;
+            m.carve(a, b);
+        }
+    }
+}
+
+// Wilson: caminatas aleatorias con borrado de ciclos; muestra uniforme de
+// todos los laberintos posibles (más lento al principio)
+fn wilson(m: &mut Carver, rng: &mut Rng) {
+    let n = m.cols * m.rows;
+    let mut in_tree = vec![false; n];
+    in_tree[rng.below(n)] = true;
+    let mut next = vec![usize::MAX; n];
+
+    let mut order: Vec<usize> = (0..n).collect();
+    rng.shuffle(&mut order);
+
+    for start in order {
+        if in_tree[start] {
+            continue;
+        }
+        // caminar hasta tocar el árbol, recordando la última salida de cada celda
+        let mut cur = start;
+        while !in_tree[cur] {
+            let nbs = m.neighbors(cur);
+            let nb = nbs[rng.below(nbs.len())];
+            next[cur] = nb;
+            cur = nb;
+        }
+        // seguir `next` desde el inicio ya descarta los ciclos
+        let mut cur = start;
+        while !in_tree[cur] {
+            m.carve(cur, next[cur]);
+            in_tree[cur] = true;
+            cur = next[cur];
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate::check_maze;
+
+    fn opts(algorithm: Algorithm) -> GenOptions {
+        GenOptions { cols: 9, rows: 7, algorithm, screamer: true }
+    }
+
+    #[test]
+    fn same_seed_same_maze() {
+        for alg in Algorithm::ALL {
+            for seed in [0, 1, 0xDEAD_BEEF] {
+                let a = generate(seed, &opts(alg), 64);
+                let b = generate(seed, &opts(alg), 64);
+                assert_eq!(a.grid, b.grid, "{} con semilla {}", alg.name(), seed);
+            }
+            assert_ne!(generate(1, &opts(alg), 64).grid, generate(2, &opts(alg), 64).grid, "{}", alg.name());
+        }
+    }
+
+    #[test]
+    fn generated_mazes_are_playable() {
+        for alg in Algorithm::ALL {
+            for seed in 0..20 {
+                let maze = generate(seed, &opts(alg), 64);
+                assert_eq!((maze.width(), maze.height()), (19, 15));
+                let issues: Vec<String> = check_maze(&maze).iter().map(|i| i.to_string()).collect();
+                assert!(issues.is_empty(), "{} con semilla {}: {:?}", alg.name(), seed, issues);
+                assert!(maze.find(|t| t == Tile::Screamer).is_some(), "{} con semilla {}", alg.name(), seed);
+            }
+        }
+    }
+}
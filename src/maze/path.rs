@@ -0,0 +1,323 @@
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::rc::Rc;
+
+use super::Maze;
+use crate::tile::Tile;
+
+// Caminos más cortos sobre el grid de celdas. Todo devuelve celdas (i,j);
+// para pasar a coordenadas de mundo se usa `cell_center_world`.
+// Las celdas sólidas no se pisan, salvo la meta: así se puede pedir un
+// camino "hasta la puerta" aunque la puerta bloquee.
+
+pub type Cell = (isize, isize);
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Connectivity {
+    Four,
+    Eight, // diagonales solo si las dos celdas ortogonales están libres
+}
+
+const ORTHO: [Cell; 4] = [(1, 0), (-1, 0), (0, 1), (0, -1)];
+const DIAG: [Cell; 4] = [(1, 1), (1, -1), (-1, 1), (-1, -1)];
+
+/// Distancia (en celdas) desde cada celda hasta el objetivo más cercano
+pub struct DistanceField {
+    w: usize,
+    h: usize,
+    conn: Connectivity,
+    dist: Vec<f32>, // INFINITY = inalcanzable
+}
+
+/// Caché de campos por tipo de tile; el grid no cambia después de crear el Maze
+pub(super) type FieldCache = HashMap<(Tile, Connectivity), Rc<DistanceField>>;
+
+impl DistanceField {
+    /// None si la celda está fuera del mapa o no llega a ningún objetivo
+    pub fn get(&self, c: Cell) -> Option<f32> {
+        if c.0 < 0 || c.1 < 0 || c.0 as usize >= self.w || c.1 as usize >= self.h {
+            return None;
+        }
+        let d = self.dist[c.1 as usize * self.w + c.0 as usize];
+        d.is_finite().then_some(d)
+    }
+
+    /// Vecino que más acerca al objetivo (None si ya está ahí o no hay camino)
+    pub fn next_step(&self, maze: &Maze, c: Cell) -> Option<Cell> {
+        if self.get(c)? == 0.0 {
+            return None;
+        }
+        maze.steps(c, self.conn, |n| self.get(n).is_some())
+            .into_iter()
+            .filter_map(|(n, cost)| Some((n, self.get(n)? + cost)))
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(n, _)| n)
+    }
+
+    /// Camino bajando por el campo desde `c` hasta el objetivo (incluye ambos extremos)
+    pub fn path_from(&self, maze: &Maze, c: Cell) -> Option<Vec<Cell>> {
+        self.get(c)?;
+        let mut path = vec![c];
+        let mut cur = c;
+        while let Some(n) = self.next_step(maze, cur) {
+            path.push(n);
+            cur = n;
+        }
+        Some(path)
+    }
+}
+
+impl Maze {
+    pub fn is_walkable(&self, c: Cell) -> bool {
+        !self.is_blocking_at(c.0, c.1)
+    }
+
+    /// Vecinos transitables de `c` con su costo (1 o √2), sin cortar esquinas
+    pub fn neighbors(&self, c: Cell, conn: Connectivity) -> Vec<(Cell, f32)> {
+        self.steps(c, conn, |n| self.is_walkable(n))
+    }
+
+    // Igual que `neighbors` pero con otro criterio para la celda de destino
+    // (las esquinas siempre se chequean contra paredes reales)
+    fn steps(&self, c: Cell, conn: Connectivity, open: impl Fn(Cell) -> bool) -> Vec<(Cell, f32)> {
+        let mut out = Vec::with_capacity(8);
+        for (di, dj) in ORTHO {
+            let n = (c.0 + di, c.1 + dj);
+            if open(n) {
+                out.push((n, 1.0));
+            }
+        }
+        if conn == Connectivity::Eight {
+            for (di, dj) in DIAG {
+                let n = (c.0 + di, c.1 + dj);
+                if open(n) && self.is_walkable((c.0 + di, c.1)) && self.is_walkable((c.0, c.1 + dj)) {
+                    out.push((n, std::f32::consts::SQRT_2));
+                }
+            }
+        }
+        out
+    }
+
+    fn in_bounds(&self, c: Cell) -> bool {
+        c.0 >= 0 && c.1 >= 0 && (c.0 as usize) < self.w && (c.1 as usize) < self.h
+    }
+
+    fn index_of(&self, c: Cell) -> usize {
+        c.1 as usize * self.w + c.0 as usize
+    }
+
+    /// Camino con menos pasos de `from` a `to` (ambos incluidos). En 8-conexo
+    /// una diagonal cuenta como un paso; para distancias reales usar `astar_path`.
+    pub fn bfs_path(&self, from: Cell, to: Cell, conn: Connectivity) -> Option<Vec<Cell>> {
+        if !self.in_bounds(from) || !self.in_bounds(to) {
+            return None;
+        }
+        let mut parent: Vec<Option<Cell>> = vec![None; self.w * self.h];
+        let mut seen = vec![false; self.w * self.h];
+        let mut queue = VecDeque::from([from]);
+        seen[self.index_of(from)] = true;
+
+        while let Some(cur) = queue.pop_front() {
+            if cur == to {
+                return Some(self.rebuild(&parent, to));
+            }
+            for (n, _) in self.steps(cur, conn, |n| n == to || self.is_walkable(n)) {
+                let k = self.index_of(n);
+                if !seen[k] {
+                    seen[k] = true;
+                    parent[k] = Some(cur);
+                    queue.push_back(n);
+                }
+            }
+        }
+        None
+    }
+
+    /// Camino más corto de `from` a `to` (ambos incluidos) con costo 1 / √2
+    pub fn astar_path(&self, from: Cell, to: Cell, conn: Connectivity) -> Option<Vec<Cell>> {
+        if !self.in_bounds(from) || !self.in_bounds(to) {
+            return None;
+        }
+        let h = |c: Cell| heuristic(c, to, conn);
+        let mut parent: Vec<Option<Cell>> = vec![None; self.w * self.h];
+        let mut g = vec![f32::INFINITY; self.w * self.h];
+        let mut open = BinaryHeap::from([Node { cost: h(from), cell: from }]);
+        g[self.index_of(from)] = 0.0;
+
+        while let Some(Node { cell: cur, .. }) = open.pop() {
+            if cur == to {
+                return Some(self.rebuild(&parent, to));
+            }
+            let base = g[self.index_of(cur)];
+            for (n, step) in self.steps(cur, conn, |n| n == to || self.is_walkable(n)) {
+                let k = self.index_of(n);
+                if base + step < g[k] {
+                    g[k] = base + step;
+                    parent[k] = Some(cur);
+                    open.push(Node { cost: g[k] + h(n), cell: n });
+                }
+            }
+        }
+        None
+    }
+
+    /// Campo de distancias a todas las celdas que cumplen `pred` (sin caché)
+    pub fn distance_field(&self, pred: impl Fn(Tile) -> bool, conn: Connectivity) -> DistanceField {
+        let mut dist = vec![f32::INFINITY; self.w * self.h];
+        let mut heap = BinaryHeap::new();
+        for j in 0..self.h {
+            for i in 0..self.w {
+                if pred(self.grid[j][i]) {
+                    let c = (i as isize, j as isize);
+                    dist[self.index_of(c)] = 0.0;
+                    heap.push(Node { cost: 0.0, cell: c });
+                }
+            }
+        }
+
+        // Dijkstra; los objetivos pueden ser sólidos (puertas), el resto no
+        while let Some(Node { cost, cell }) = heap.pop() {
+            if cost > dist[self.index_of(cell)] {
+                continue;
+            }
+            for (n, step) in self.neighbors(cell, conn) {
+                let k = self.index_of(n);
+                if cost + step < dist[k] {
+                    dist[k] = cost + step;
+                    heap.push(Node { cost: dist[k], cell: n });
+                }
+            }
+        }
+        DistanceField { w: self.w, h: self.h, conn, dist }
+    }
+
+    /// Campo de distancias a un tipo de tile; se calcula una vez por Maze
+    pub fn distance_to(&self, tile: Tile, conn: Connectivity) -> Rc<DistanceField> {
+        if let Some(field) = self.fields.borrow().get(&(tile, conn)) {
+            return Rc::clone(field);
+        }
+        let field = Rc::new(self.distance_field(|t| t == tile, conn));
+        self.fields.borrow_mut().insert((tile, conn), Rc::clone(&field));
+        field
+    }
+
+    fn rebuild(&self, parent: &[Option<Cell>], to: Cell) -> Vec<Cell> {
+        let mut path = vec![to];
+        let mut cur = to;
+        while let Some(p) = parent[self.index_of(cur)] {
+            path.push(p);
+            cur = p;
+        }
+        path.reverse();
+        path
+    }
+}
+
+// Manhattan en 4-conexo, octil en 8-conexo (ambas admisibles)
+fn heuristic(a: Cell, b: Cell, conn: Connectivity) -> f32 {
+    let (dx, dy) = ((a.0 - b.0).abs() as f32, (a.1 - b.1).abs() as f32);
+    match conn {
+        Connectivity::Four => dx + dy,
+        Connectivity::Eight => dx.max(dy) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dy),
+    }
+}
+
+// Entrada del heap: BinaryHeap es de máximos, así que se invierte el orden
+struct Node {
+    cost: f32,
+    cell: Cell,
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Self) -> bool {
+        self.cost == other.cost
+    }
+}
+
+impl Eq for Node {}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Node {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.cost.total_cmp(&self.cost)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze(rows: &[&str]) -> Maze {
+        Maze::parse(&rows.join("\n"), 64)
+    }
+
+    // largo real de un camino: 1 por paso recto, √2 por diagonal
+    fn cost(path: &[Cell]) -> f32 {
+        path.windows(2)
+            .map(|w| if w[0].0 != w[1].0 && w[0].1 != w[1].1 { std::f32::consts::SQRT_2 } else { 1.0 })
+            .sum()
+    }
+
+    #[test]
+    fn diagonals_do_not_cut_wall_corners() {
+        let m = maze(&[" # ", "   "]);
+        let around: Vec<Cell> = m.neighbors((0, 0), Connectivity::Eight).into_iter().map(|(c, _)| c).collect();
+        assert!(around.contains(&(0, 1)));
+        assert!(!around.contains(&(1, 1)), "la diagonal pasa por la esquina de la pared");
+
+        // rodea la pared por abajo sin ninguna diagonal
+        let path = m.astar_path((0, 0), (2, 0), Connectivity::Eight).unwrap();
+        assert_eq!(path, vec![(0, 0), (0, 1), (1, 1), (2, 1), (2, 0)]);
+    }
+
+    #[test]
+    fn astar_and_dijkstra_agree_on_cost() {
+        let m = maze(&[
+            "       ",
+            " ### # ",
+            "   #   ",
+            " # # ##",
+            " #    P",
+        ]);
+        let goal = m.find(|t| t == Tile::Spawn).unwrap();
+        for conn in [Connectivity::Four, Connectivity::Eight] {
+            let field = m.distance_field(|t| t == Tile::Spawn, conn);
+            for j in 0..m.height() as isize {
+                for i in 0..m.width() as isize {
+                    let Some(d) = field.get((i, j)) else { continue; };
+                    let path = m.astar_path((i, j), goal, conn).unwrap();
+                    assert!((cost(&path) - d).abs() < 1e-4, "{:?} {:?}: A* {} / Dijkstra {}", conn, (i, j), cost(&path), d);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn solid_door_is_a_reachable_goal() {
+        let m = maze(&["#####", "#P D#", "#####"]);
+        assert!(m.is_blocking_at(3, 1));
+        let field = m.distance_to(Tile::Door, Connectivity::Four);
+        assert_eq!(field.get((1, 1)), Some(2.0));
+        assert_eq!(field.path_from(&m, (1, 1)), Some(vec![(1, 1), (2, 1), (3, 1)]));
+        assert_eq!(m.astar_path((1, 1), (3, 1), Connectivity::Four), Some(vec![(1, 1), (2, 1), (3, 1)]));
+    }
+
+    #[test]
+    fn field_cache_is_keyed_by_tile_and_connectivity() {
+        let m = maze(&["P  ", "   ", "  D"]);
+        let four = m.distance_to(Tile::Door, Connectivity::Four);
+        let eight = m.distance_to(Tile::Door, Connectivity::Eight);
+        let spawn = m.distance_to(Tile::Spawn, Connectivity::Four);
+        assert!(Rc::ptr_eq(&four, &m.distance_to(Tile::Door, Connectivity::Four)));
+        assert!(!Rc::ptr_eq(&four, &eight));
+        assert_eq!(four.get((0, 0)), Some(4.0));
+        assert_eq!(eight.get((0, 0)), Some(2.0 * std::f32::consts::SQRT_2));
+        assert_eq!(spawn.get((0, 0)), Some(0.0));
+        assert_eq!(spawn.get((2, 2)), None); // la puerta es sólida: solo cuenta como meta, no como paso
+    }
+}
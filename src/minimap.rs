@@ -1,54 +1,575 @@
 use raylib::prelude::*;
-use crate::{maze::Maze, player::Player};
+use crate::{explored::Explored, maze::Maze, player::Player, screamer::has_los};
 
+// Minimapa en una esquina y mapa completo (acción `Map`). Las celdas pasan de
+// mundo a pantalla con un `Xform` (centro, escala y giro), así el modo fijo,
+// el giratorio y el mapa completo comparten el mismo dibujo.
+
+pub const ZOOM_MIN: f32 = 0.5;
+pub const ZOOM_MAX: f32 = 4.0;
+
+// lugar que queda libre abajo para la línea de ayuda del juego
+const FOOTER_PX: i32 = 26;
+
+// el cono de visión usa a lo sumo esta cantidad de rayos (hay uno por columna de la vista 3D)
+const CONE_RAYS: usize = 160;
+// lados del polígono con el que se recortan las celdas del minimapa circular
+const DISC_SIDES: usize = 48;
+
+/// Qué celdas muestra el minimapa (opción del usuario)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MapFog {
+    Remembered,  // lo visto alguna vez, atenuado; lo visible ahora, normal
+    VisibleOnly, // solo lo que alcanzan los rayos en este frame
+    Off,         // todo el nivel (como antes)
+}
+
+impl MapFog {
+    pub const ALL: [MapFog; 3] = [MapFog::Remembered, MapFog::VisibleOnly, MapFog::Off];
+
+    /// Valor en `settings.cfg`
+    pub fn name(self) -> &'static str {
+        match self {
+            MapFog::Remembered => "remembered",
+            MapFog::VisibleOnly => "visible",
+            MapFog::Off => "off",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MapFog::Remembered => "recordado",
+            MapFog::VisibleOnly => "solo lo visible",
+            MapFog::Off => "sin niebla",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MapMode {
+    NorthUp,  // norte arriba; sigue al jugador si el nivel no entra entero
+    Rotating, // centrado en el jugador, que siempre mira hacia arriba
+}
+
+impl MapMode {
+    pub const ALL: [MapMode; 2] = [MapMode::NorthUp, MapMode::Rotating];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MapMode::NorthUp => "north_up",
+            MapMode::Rotating => "rotating",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MapMode::NorthUp => "fijo (norte arriba)",
+            MapMode::Rotating => "gira con el jugador",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MapShape {
+    Square,
+    Circle,
+}
+
+impl MapShape {
+    pub const ALL: [MapShape; 2] = [MapShape::Square, MapShape::Circle];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            MapShape::Square => "square",
+            MapShape::Circle => "circle",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            MapShape::Square => "cuadrado",
+            MapShape::Circle => "círculo",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Corner {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl Corner {
+    pub const ALL: [Corner; 4] = [Corner::TopLeft, Corner::TopRight, Corner::BottomLeft, Corner::BottomRight];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Corner::TopLeft => "top_left",
+            Corner::TopRight => "top_right",
+            Corner::BottomLeft => "bottom_left",
+            Corner::BottomRight => "bottom_right",
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Corner::TopLeft => "arriba a la izquierda",
+            Corner::TopRight => "arriba a la derecha",
+            Corner::BottomLeft => "abajo a la izquierda",
+            Corner::BottomRight => "abajo a la derecha",
+        }
+    }
+}
+
+/// Tipo de marcador; define el ícono y el color
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum MarkerKind {
+    Enemy,
+    Item,
+    Objective,
+    Waypoint,
+}
+
+/// Cuándo se dibuja un marcador
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Reveal {
+    Always,
+    Explored,             // su celda ya se vio alguna vez
+    Sight,                // hay línea de vista desde el jugador
+    Hearing(f32),         // a esta distancia del jugador o menos (en celdas)
+    SightOrHearing(f32),
+}
+
+/// Algo del nivel que se marca en el mapa (`World::markers` arma los de las entidades)
+#[derive(Clone, Copy, Debug)]
+pub struct Marker {
+    pub kind: MarkerKind,
+    pub pos: Vector2, // píxeles de mundo
+    pub reveal: Reveal,
+}
+
+impl Marker {
+    pub fn is_revealed(&self, maze: &Maze, player_pos: Vector2, explored: &Explored) -> bool {
+        let bs = maze.block_size() as f32;
+        let sight = || has_los(maze, player_pos, self.pos);
+        let near = |cells: f32| (self.pos - player_pos).length() <= cells * bs;
+        match self.reveal {
+            Reveal::Always => true,
+            Reveal::Explored => {
+                let (i, j) = maze.world_to_cell(self.pos);
+                explored.is_seen(i as i32, j as i32)
+            }
+            Reveal::Sight => sight(),
+            Reveal::Hearing(cells) => near(cells),
+            Reveal::SightOrHearing(cells) => near(cells) || sight(),
+        }
+    }
+}
+
+/// Ayudas para orientarse (ver `assist`); `Guide::default()` = nada
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Guide<'a> {
+    pub crumbs: &'a [Vector2], // píxeles de mundo
+    pub path: &'a [Vector2],   // camino de la pista, vacío si no hay
+    pub path_alpha: f32,       // 0..1, la pista se desvanece
+}
+
+#[derive(Clone, Copy, Debug)]
 pub struct MiniMapCfg {
-    pub tile_px: i32,
+    pub tile_px: i32, // lado de una celda con zoom 1
     pub margin: i32,
-    pub scale: f32,
+    pub size: i32,    // lado (o diámetro) del recuadro en píxeles
+    pub scale: f32,   // zoom, entre ZOOM_MIN y ZOOM_MAX
+    pub mode: MapMode,
+    pub shape: MapShape,
+    pub corner: Corner,
+    pub opacity: f32, // 0..1
+    pub fog: MapFog,
+    pub enemies: bool, // false = los marcadores de enemigos no se dibujan nunca
+}
+
+/// Un paso de zoom (`dir` = +1 acerca, -1 aleja)
+pub fn zoom_step(scale: f32, dir: i32) -> f32 {
+    (scale * 1.25f32.powi(dir)).clamp(ZOOM_MIN, ZOOM_MAX)
 }
 
+// Mundo (en celdas) → pantalla: `focus` cae en `center`, girado `rot` radianes
+struct Xform {
+    center: Vector2,
+    focus: Vector2,
+    tile: f32, // píxeles por celda
+    rot: f32,
+}
+
+impl Xform {
+    fn apply(&self, p: Vector2) -> Vector2 {
+        let v = (p - self.focus) * self.tile;
+        let (s, c) = self.rot.sin_cos();
+        self.center + Vector2::new(v.x * c - v.y * s, v.x * s + v.y * c)
+    }
+}
+
+/// Minimapa en la esquina elegida. Retorna el recuadro que ocupa (para ubicar el HUD).
 pub fn draw_minimap(
     d: &mut RaylibDrawHandle,
     maze: &Maze,
     player: &Player,
-    _enemies: Option<&[()]>,
-    cfg: MiniMapCfg,
+    explored: &Explored,
+    markers: &[Marker],
+    guide: &Guide,
+    cfg: &MiniMapCfg,
+) -> Rectangle {
+    let (sw, sh) = (d.get_screen_width(), d.get_screen_height());
+    let size = cfg.size;
+    let x = match cfg.corner {
+        Corner::TopLeft | Corner::BottomLeft => cfg.margin,
+        Corner::TopRight | Corner::BottomRight => sw - size - cfg.margin,
+    };
+    let y = match cfg.corner {
+        Corner::TopLeft | Corner::TopRight => cfg.margin,
+        Corner::BottomLeft | Corner::BottomRight => sh - size - cfg.margin - FOOTER_PX,
+    };
+    let half = size as f32 * 0.5;
+    let center = Vector2::new(x as f32 + half, y as f32 + half);
+
+    let tile = cfg.tile_px as f32 * cfg.scale;
+    let bs = maze.block_size() as f32;
+    let level = Vector2::new(maze.width() as f32, maze.height() as f32);
+    let (focus, rot) = match cfg.mode {
+        MapMode::NorthUp if level.x * tile <= size as f32 && level.y * tile <= size as f32 => (level * 0.5, 0.0),
+        MapMode::NorthUp => (player.pos / bs, 0.0),
+        MapMode::Rotating => (player.pos / bs, -std::f32::consts::FRAC_PI_2 - player.a),
+    };
+    let xf = Xform { center, focus, tile, rot };
+
+    let bg = Color::new(0, 0, 0, (160.0 * cfg.opacity) as u8);
+    match cfg.shape {
+        MapShape::Square => d.draw_rectangle(x, y, size, size, bg),
+        MapShape::Circle => d.draw_circle_v(center, half, bg),
+    }
+    {
+        let mut clip = d.begin_scissor_mode(x, y, size, size);
+        // en el círculo las celdas del borde se recortan al disco; lo demás
+        // se salta si su centro queda afuera
+        let disc = (cfg.shape == MapShape::Circle).then_some(half);
+        let radius = disc.map(|r| r - tile * 0.5);
+        draw_cells(&mut clip, maze, explored, cfg.fog, &xf, cfg.opacity, disc);
+        draw_guide(&mut clip, guide, bs, &xf, cfg.opacity, radius);
+        draw_view_cone(&mut clip, explored, bs, &xf, cfg.opacity, disc);
+        for m in shown(markers, maze, player, explored, cfg.enemies) {
+            let p = xf.apply(m.pos / bs);
+            if radius.is_none_or(|r| (p - center).length() <= r) {
+                draw_marker(&mut clip, m.kind, p, xf.tile, cfg.opacity);
+            }
+        }
+        draw_player(&mut clip, player, bs, &xf, cfg.opacity);
+    }
+    let frame = faded(Color::WHITE, cfg.opacity);
+    match cfg.shape {
+        MapShape::Square => d.draw_rectangle_lines(x - 1, y - 1, size + 2, size + 2, frame),
+        MapShape::Circle => d.draw_circle_lines_v(center, half, frame),
+    }
+    Rectangle::new(x as f32, y as f32, size as f32, size as f32)
+}
+
+/// Todo el nivel, norte arriba, sobre la pantalla entera
+pub fn draw_full_map(
+    d: &mut RaylibDrawHandle,
+    maze: &Maze,
+    player: &Player,
+    explored: &Explored,
+    markers: &[Marker],
+    guide: &Guide,
+    cfg: &MiniMapCfg,
+) {
+    let (sw, sh) = (d.get_screen_width(), d.get_screen_height());
+    d.draw_rectangle(0, 0, sw, sh, Color::new(0, 0, 0, 210));
+    d.draw_text("MAPA", 40, 24, 28, Color::RAYWHITE);
+
+    let level = Vector2::new(maze.width() as f32, maze.height() as f32);
+    let tile = ((sw - 80) as f32 / level.x).min((sh - 140) as f32 / level.y);
+    let center = Vector2::new(sw as f32 * 0.5, sh as f32 * 0.5 + 10.0);
+    let xf = Xform { center, focus: level * 0.5, tile, rot: 0.0 };
+    let bs = maze.block_size() as f32;
+    draw_cells(d, maze, explored, cfg.fog, &xf, 1.0, None);
+    draw_guide(d, guide, bs, &xf, 1.0, None);
+    draw_view_cone(d, explored, bs, &xf, 1.0, None);
+    for m in shown(markers, maze, player, explored, cfg.enemies) {
+        draw_marker(d, m.kind, xf.apply(m.pos / bs), xf.tile, 1.0);
+    }
+    draw_player(d, player, bs, &xf, 1.0);
+}
+
+// `radius`: en el minimapa circular, las celdas que cruzan el borde se recortan
+fn draw_cells(
+    d: &mut impl RaylibDraw,
+    maze: &Maze,
+    explored: &Explored,
+    fog: MapFog,
+    xf: &Xform,
+    opacity: f32,
+    radius: Option<f32>,
 ) {
-    let tile = cfg.tile_px;
-    let ox = cfg.margin;
-    let oy = cfg.margin;
-    let w = (maze.width() as i32) * tile;
-    let h = (maze.height() as i32) * tile;
-
-    d.draw_rectangle(ox - 4, oy - 4, w + 8, h + 8, Color::new(0, 0, 0, 160));
-    d.draw_rectangle_lines(ox - 4, oy - 4, w + 8, h + 8, Color::WHITE);
-
-    for j in 0..maze.height() {
-        for i in 0..maze.width() {
-            let c = maze.cell_i32(i as i32, j as i32);
-            let color = match c {
-                '#' => Color::DARKGRAY,
-                'D' => Color::GOLD,
-                'P' => Color::DARKBLUE,
-                'S' => Color::MAROON,
-                _ => Color::BLACK,
+    // medio píxel de más para que no queden rayas entre celdas al girar
+    let side = xf.tile + 0.5;
+    let deg = xf.rot.to_degrees();
+    for j in 0..maze.height() as i32 {
+        for i in 0..maze.width() as i32 {
+            let color = maze.cell_i32(i, j).def().minimap;
+            let color = match fog {
+                MapFog::Off => color,
+                _ if explored.is_visible(i, j) => color,
+                MapFog::Remembered if explored.is_seen(i, j) => dimmed(color),
+                _ => continue, // sin ver: queda el fondo
             };
-            d.draw_rectangle(ox + (i as i32) * tile, oy + (j as i32) * tile, tile, tile, color);
+            let p = xf.apply(Vector2::new(i as f32 + 0.5, j as f32 + 0.5));
+            if let Some(r) = radius {
+                if (p - xf.center).length() > r + side * std::f32::consts::FRAC_1_SQRT_2 {
+                    continue;
+                }
+                let corners = cell_corners(p, side, xf.rot);
+                if corners.iter().any(|&c| (c - xf.center).length() > r) {
+                    let piece = clip_to_disc(&corners, xf.center, r);
+                    if piece.len() >= 3 {
+                        d.draw_triangle_fan(&piece, faded(color, opacity));
+                    }
+                    continue;
+                }
+            }
+            d.draw_rectangle_pro(
+                Rectangle::new(p.x, p.y, side, side),
+                Vector2::new(side * 0.5, side * 0.5),
+                deg,
+                faded(color, opacity),
+            );
         }
     }
+}
 
-    // jugador
-    let bs = maze.block_size() as f32;
-    let px = ox as f32 + (player.pos.x / bs) * tile as f32;
-    let py = oy as f32 + (player.pos.y / bs) * tile as f32;
-    d.draw_circle(px as i32, py as i32, (tile as f32) * 0.35, Color::SKYBLUE);
-
-    // FOV líneas
-    let len = (tile as f32) * 1.5;
-    let a0 = player.a - 0.25;
-    let a1 = player.a + 0.25;
-    let (x0, y0) = (px + len * a0.cos(), py + len * a0.sin());
-    let (x1, y1) = (px + len * a1.cos(), py + len * a1.sin());
-    d.draw_line(px as i32, py as i32, x0 as i32, y0 as i32, Color::RAYWHITE);
-    d.draw_line(px as i32, py as i32, x1 as i32, y1 as i32, Color::RAYWHITE);
+// Migas y, encima, el camino de la pista. En el minimapa circular se saltan
+// las migas y los tramos que caen fuera de `radius`.
+fn draw_guide(d: &mut impl RaylibDraw, guide: &Guide, bs: f32, xf: &Xform, opacity: f32, radius: Option<f32>) {
+    let inside = |p: Vector2| radius.is_none_or(|r| (p - xf.center).length() <= r);
+    let crumb = faded(Color::new(235, 225, 190, 200), opacity);
+    for &c in guide.crumbs {
+        let p = xf.apply(c / bs);
+        if inside(p) {
+            d.draw_circle_v(p, (xf.tile * 0.12).max(1.5), crumb);
+        }
+    }
+
+    if guide.path_alpha <= 0.0 {
+        return;
+    }
+    let color = faded(Color::GOLD, opacity * guide.path_alpha);
+    let thick = (xf.tile * 0.2).max(2.0);
+    let points: Vec<Vector2> = guide.path.iter().map(|&c| xf.apply(c / bs)).collect();
+    for w in points.windows(2) {
+        if inside(w[0]) && inside(w[1]) {
+            d.draw_line_ex(w[0], w[1], thick, color);
+        }
+    }
+    if let Some(&door) = points.last().filter(|&&p| inside(p)) {
+        d.draw_circle_v(door, thick * 1.5, color);
+    }
+}
+
+// Lo que ve la cámara en el último frame: un abanico desde el ojo hasta donde
+// pegó cada rayo de `render_3d_scene` (mismo FOV y mismas paredes que la vista 3D).
+// `radius`: en el minimapa circular los rayos se cortan en el borde.
+fn draw_view_cone(d: &mut impl RaylibDraw, explored: &Explored, bs: f32, xf: &Xform, opacity: f32, radius: Option<f32>) {
+    let (eye, rays) = explored.view_rays();
+    if rays.len() < 2 {
+        return;
+    }
+    let step = rays.len().div_ceil(CONE_RAYS);
+    let mut ends: Vec<Vector2> = rays.iter().step_by(step).map(|&r| xf.apply(r / bs)).collect();
+    if (rays.len() - 1) % step != 0 {
+        ends.push(xf.apply(rays[rays.len() - 1] / bs)); // el borde derecho siempre
+    }
+    let eye = xf.apply(eye / bs);
+    if let Some(r) = radius {
+        for end in &mut ends {
+            *end = clip_to_circle(eye, *end, xf.center, r);
+        }
+    }
+
+    let fill = faded(Color::new(255, 240, 160, 70), opacity);
+    let edge = faded(Color::new(255, 240, 160, 200), opacity);
+    for w in ends.windows(2) {
+        // los rayos van en sentido horario en pantalla: raylib quiere antihorario
+        d.draw_triangle(eye, w[1], w[0], fill);
+        d.draw_line_v(w[0], w[1], edge);
+    }
+    d.draw_line_v(eye, ends[0], edge);
+    d.draw_line_v(eye, ends[ends.len() - 1], edge);
+}
+
+// Marcadores que corresponde dibujar ahora
+fn shown<'a>(
+    markers: &'a [Marker],
+    maze: &'a Maze,
+    player: &'a Player,
+    explored: &'a Explored,
+    enemies: bool,
+) -> impl Iterator<Item = &'a Marker> {
+    markers
+        .iter()
+        .filter(move |m| (enemies || m.kind != MarkerKind::Enemy) && m.is_revealed(maze, player.pos, explored))
+}
+
+fn draw_marker(d: &mut impl RaylibDraw, kind: MarkerKind, p: Vector2, tile: f32, opacity: f32) {
+    let r = (tile * 0.5).max(3.0);
+    match kind {
+        MarkerKind::Enemy => d.draw_poly(p, 3, r, -90.0, faded(Color::RED, opacity)),
+        MarkerKind::Item => d.draw_poly(p, 4, r * 0.8, 0.0, faded(Color::LIME, opacity)),
+        MarkerKind::Objective => d.draw_poly(p, 5, r * 1.2, -90.0, faded(Color::ORANGE, opacity)),
+        MarkerKind::Waypoint => d.draw_circle_lines_v(p, r * 0.8, faded(Color::WHITE, opacity)),
+    }
+}
+
+fn draw_player(d: &mut impl RaylibDraw, player: &Player, bs: f32, xf: &Xform, opacity: f32) {
+    let p = xf.apply(player.pos / bs);
+    d.draw_circle_v(p, xf.tile * 0.35, faded(Color::SKYBLUE, opacity));
+}
+
+// Recorta el segmento `from`→`to` (con `from` adentro) al círculo `center`, `r`
+fn clip_to_circle(from: Vector2, to: Vector2, center: Vector2, r: f32) -> Vector2 {
+    if (to - center).length() <= r {
+        return to;
+    }
+    // |from + t·dir - center| = r, la raíz positiva
+    let dir = to - from;
+    let f = from - center;
+    let (a, b, c) = (dir.dot(dir), 2.0 * f.dot(dir), f.dot(f) - r * r);
+    let disc = (b * b - 4.0 * a * c).max(0.0);
+    let t = ((-b + disc.sqrt()) / (2.0 * a)).clamp(0.0, 1.0);
+    from + dir * t
+}
+
+// Esquinas de la celda de lado `side` centrada en `p`, en sentido horario en pantalla
+fn cell_corners(p: Vector2, side: f32, rot: f32) -> [Vector2; 4] {
+    let h = side * 0.5;
+    let (s, c) = rot.sin_cos();
+    [(-h, -h), (h, -h), (h, h), (-h, h)].map(|(x, y)| p + Vector2::new(x * c - y * s, x * s + y * c))
+}
+
+// Lo que queda del polígono convexo `poly` (horario en pantalla) dentro del
+// círculo, aproximado por un polígono de DISC_SIDES lados (Sutherland–Hodgman).
+// Sale en antihorario, como lo quiere `draw_triangle_fan`.
+fn clip_to_disc(poly: &[Vector2], center: Vector2, r: f32) -> Vec<Vector2> {
+    let cross = |a: Vector2, b: Vector2| a.x * b.y - a.y * b.x;
+    let mut out = poly.to_vec();
+    for k in 0..DISC_SIDES {
+        if out.is_empty() {
+            break;
+        }
+        let angle = |k: usize| k as f32 * std::f32::consts::TAU / DISC_SIDES as f32;
+        let a = center + Vector2::new(angle(k).cos(), angle(k).sin()) * r;
+        let b = center + Vector2::new(angle(k + 1).cos(), angle(k + 1).sin()) * r;
+        // > 0: del lado del centro
+        let side = |p: Vector2| cross(b - a, p - a);
+        let input = std::mem::take(&mut out);
+        for (i, &e) in input.iter().enumerate() {
+            let s = input[(i + input.len() - 1) % input.len()];
+            let (ds, de) = (side(s), side(e));
+            if (ds >= 0.0) != (de >= 0.0) {
+                out.push(s + (e - s) * (ds / (ds - de)));
+            }
+            if de >= 0.0 {
+                out.push(e);
+            }
+        }
+    }
+    out.reverse();
+    out
+}
+
+// Celda recordada pero fuera de la vista actual
+fn dimmed(c: Color) -> Color {
+    Color::new(c.r / 3, c.g / 3, c.b / 3, c.a)
+}
+
+fn faded(c: Color, opacity: f32) -> Color {
+    Color::new(c.r, c.g, c.b, (c.a as f32 * opacity) as u8)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::spawn_player_from_maze;
+
+    // el jugador en (1,1); (5,1) a la vista por el pasillo, (1,3) a dos celdas tras una pared
+    fn corridors() -> (Maze, Player) {
+        let maze = Maze::parse("#######\n#P    #\n#######\n#     #\n#######", 64);
+        let player = spawn_player_from_maze(&maze);
+        (maze, player)
+    }
+
+    fn at(maze: &Maze, cell: (isize, isize), kind: MarkerKind, reveal: Reveal) -> Marker {
+        Marker { kind, pos: maze.cell_center_world(cell), reveal }
+    }
+
+    #[test]
+    fn every_reveal_mode() {
+        let (maze, player) = corridors();
+        let mut explored = Explored::unsaved(&maze);
+        let revealed = |m: Marker, explored: &Explored| m.is_revealed(&maze, player.pos, explored);
+        let (open, behind) = ((5, 1), (1, 3));
+        let kind = MarkerKind::Item;
+
+        assert!(revealed(at(&maze, behind, kind, Reveal::Always), &explored));
+
+        assert!(!revealed(at(&maze, behind, kind, Reveal::Explored), &explored));
+        explored.mark(1, 3);
+        assert!(revealed(at(&maze, behind, kind, Reveal::Explored), &explored));
+
+        assert!(revealed(at(&maze, open, kind, Reveal::Sight), &explored));
+        assert!(!revealed(at(&maze, behind, kind, Reveal::Sight), &explored));
+
+        assert!(revealed(at(&maze, behind, kind, Reveal::Hearing(3.0)), &explored));
+        assert!(!revealed(at(&maze, behind, kind, Reveal::Hearing(1.0)), &explored));
+        assert!(!revealed(at(&maze, open, kind, Reveal::Hearing(1.0)), &explored));
+
+        assert!(revealed(at(&maze, open, kind, Reveal::SightOrHearing(1.0)), &explored));
+        assert!(revealed(at(&maze, behind, kind, Reveal::SightOrHearing(3.0)), &explored));
+        assert!(!revealed(at(&maze, behind, kind, Reveal::SightOrHearing(1.0)), &explored));
+    }
+
+    #[test]
+    fn enemies_toggle_hides_only_enemies() {
+        let (maze, player) = corridors();
+        let explored = Explored::unsaved(&maze);
+        let markers = [
+            at(&maze, (4, 1), MarkerKind::Enemy, Reveal::Always),
+            at(&maze, (5, 1), MarkerKind::Item, Reveal::Always),
+            at(&maze, (1, 3), MarkerKind::Objective, Reveal::Always),
+            at(&maze, (1, 1), MarkerKind::Waypoint, Reveal::Always),
+        ];
+        let kinds = |enemies| shown(&markers, &maze, &player, &explored, enemies).map(|m| m.kind).collect::<Vec<_>>();
+        assert_eq!(kinds(true).len(), 4);
+        assert_eq!(kinds(false), [MarkerKind::Item, MarkerKind::Objective, MarkerKind::Waypoint]);
+    }
+
+    #[test]
+    fn border_cells_are_clipped_to_the_disc() {
+        let center = Vector2::new(50.0, 50.0);
+        // del todo adentro: queda igual (dado vuelta)
+        let inner = cell_corners(center, 10.0, 0.3);
+        let piece = clip_to_disc(&inner, center, 40.0);
+        assert_eq!(piece.len(), 4);
+        assert!(inner.iter().all(|c| piece.contains(c)));
+
+        // cruzando el borde: nada sobresale del círculo
+        let edge = cell_corners(Vector2::new(88.0, 55.0), 12.0, 0.7);
+        let piece = clip_to_disc(&edge, center, 40.0);
+        assert!(piece.len() >= 3);
+        assert!(piece.iter().all(|&p| (p - center).length() <= 40.0 + 1e-3));
+
+        // afuera: no queda nada
+        let outer = cell_corners(Vector2::new(120.0, 50.0), 10.0, 0.0);
+        assert!(clip_to_disc(&outer, center, 40.0).is_empty());
+    }
 }
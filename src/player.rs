@@ -1,7 +1,8 @@
 use raylib::prelude::*;
-use raylib::consts::{GamepadAxis, GamepadButton};
+use crate::input::InputSnapshot;
 use crate::maze::Maze;
 
+#[derive(Clone)]
 pub struct Player {
     pub pos: Vector2,
     pub a: f32,        // ángulo (radianes)
@@ -17,78 +18,30 @@ impl Player {
         }
     }
 
-    pub fn update(&mut self, d: &mut RaylibDrawHandle, maze: &Maze, dt: f32) {
-        // ---- rotación con mouse ----
-        let md = d.get_mouse_delta();
-        self.a += md.x * 0.0032; // sensibilidad mouse
-
-        // ---- rotación con gamepad (stick derecho + D-Pad) ----
-        if d.is_gamepad_available(0) {
-            // derecha/izquierda del stick derecho
-            let rx = deadzone(d.get_gamepad_axis_movement(0, GamepadAxis::GAMEPAD_AXIS_RIGHT_X), 0.18);
-            // velocidad de giro por segundo (rad/s) escalada por el eje
-            let turn_speed_gamepad = 3.6;
-            self.a += rx * turn_speed_gamepad * dt;
-
-            // giro fino con D-Pad
-            let turn_speed_dpad = 2.6;
-            if d.is_gamepad_button_down(0, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_LEFT) {
-                self.a -= turn_speed_dpad * dt;
-            }
-            if d.is_gamepad_button_down(0, GamepadButton::GAMEPAD_BUTTON_LEFT_FACE_RIGHT) {
-                self.a += turn_speed_dpad * dt;
-            }
-        }
-
-        // ---- rotación con teclado (← / →) ----
-        let turn_speed_keys: f32 = 2.8; // rad/s
-        if d.is_key_down(KeyboardKey::KEY_LEFT)  { self.a -= turn_speed_keys * dt; }
-        if d.is_key_down(KeyboardKey::KEY_RIGHT) { self.a += turn_speed_keys * dt; }
+    /// Un paso de simulación: gira y se mueve según la entrada, chocando con el mapa
+    pub fn update(&mut self, input: &InputSnapshot, maze: &Maze, dt: f32) {
+        self.a += input.look + input.turn * dt;
 
         // mantener ángulo acotado
         if self.a > std::f32::consts::PI { self.a -= 2.0 * std::f32::consts::PI; }
         if self.a < -std::f32::consts::PI { self.a += 2.0 * std::f32::consts::PI; }
 
-        // ---- movimiento (teclado + gamepad) ----
-        // dir.x = forward/back ; dir.y = strafe
-        let mut dir = Vector2::zero();
-
-        // teclado
-        if d.is_key_down(KeyboardKey::KEY_W) { dir.x += 1.0; }
-        if d.is_key_down(KeyboardKey::KEY_S) { dir.x -= 1.0; }
-        if d.is_key_down(KeyboardKey::KEY_D) { dir.y += 1.0; }
-        if d.is_key_down(KeyboardKey::KEY_A) { dir.y -= 1.0; }
-
-        // gamepad stick izquierdo
-        if d.is_gamepad_available(0) {
-            let lx = deadzone(d.get_gamepad_axis_movement(0, GamepadAxis::GAMEPAD_AXIS_LEFT_X), 0.18);
-            let ly = deadzone(d.get_gamepad_axis_movement(0, GamepadAxis::GAMEPAD_AXIS_LEFT_Y), 0.18);
-            // En la mayoría de mandos, arriba = -1, abajo = +1
-            let forward = -ly;
-            let strafe  =  lx;
-            dir.x += forward;
-            dir.y += strafe;
-        }
-
-        // velocidad base + sprint con L1 (opcional)
+        // velocidad base + sprint
         let mut speed = self.walk_speed;
-        if d.is_gamepad_available(0) && d.is_gamepad_button_down(0, GamepadButton::GAMEPAD_BUTTON_LEFT_TRIGGER_1) {
-            speed *= 1.35;
-        }
-        if d.is_key_down(KeyboardKey::KEY_LEFT_SHIFT) {
+        if input.sprint {
             speed *= 1.35;
         }
 
-        // transformar a espacio mundo según el ángulo
+        // dir.x = forward/back ; dir.y = strafe, a espacio mundo según el ángulo
         let mut mv = Vector2::zero();
-        if dir.x != 0.0 {
-            mv.x += self.a.cos() * dir.x;
-            mv.y += self.a.sin() * dir.x;
+        if input.forward != 0.0 {
+            mv.x += self.a.cos() * input.forward;
+            mv.y += self.a.sin() * input.forward;
         }
-        if dir.y != 0.0 {
+        if input.strafe != 0.0 {
             // strafe (a + 90°)
-            mv.x += (self.a + std::f32::consts::FRAC_PI_2).cos() * dir.y;
-            mv.y += (self.a + std::f32::consts::FRAC_PI_2).sin() * dir.y;
+            mv.x += (self.a + std::f32::consts::FRAC_PI_2).cos() * input.strafe;
+            mv.y += (self.a + std::f32::consts::FRAC_PI_2).sin() * input.strafe;
         }
 
         if mv.length() > 0.0 {
@@ -97,7 +50,16 @@ impl Player {
         }
     }
 
-    fn try_move(&mut self, maze: &Maze, delta: Vector2) {
+    /// Punto medio bloque adelante: lo que "toca" al usar (puertas)
+    pub fn use_point(&self, maze: &Maze) -> Vector2 {
+        let bs = maze.block_size() as f32;
+        Vector2 {
+            x: self.pos.x + self.a.cos() * bs * 0.5,
+            y: self.pos.y + self.a.sin() * bs * 0.5,
+        }
+    }
+
+    pub fn try_move(&mut self, maze: &Maze, delta: Vector2) {
         let bs = maze.block_size() as f32;
         let r = 12.0; // radio de colisión
         let nx = self.pos.x + delta.x;
@@ -113,7 +75,18 @@ impl Player {
     }
 }
 
-fn collides(maze: &Maze, x: f32, y: f32, r: f32, bs: f32) -> bool {
+/// Jugador en la 'P' del mapa (o en la celda (1,1) si no hay)
+pub fn spawn_player_from_maze(maze: &Maze) -> Player {
+    if let Some(cell) = maze.find(|t| t.def().spawn) {
+        let p = maze.cell_center_world(cell);
+        Player::new(p)
+    } else {
+        let bs = maze.block_size() as f32;
+        Player::new(Vector2::new(bs * 1.5, bs * 1.5))
+    }
+}
+
+pub fn collides(maze: &Maze, x: f32, y: f32, r: f32, bs: f32) -> bool {
     let tests = [
         (x - r, y - r),
         (x + r, y - r),
@@ -138,7 +111,36 @@ fn norm(v: Vector2) -> Vector2 {
     }
 }
 
-// Deadzone simétrica para sticks analógicos
-fn deadzone(v: f32, dz: f32) -> f32 {
-    if v.abs() < dz { 0.0 } else { v }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room() -> Maze {
+        Maze::parse("#####\n#   #\n#   #\n#####", 64)
+    }
+
+    #[test]
+    fn collides_checks_the_four_corners() {
+        let m = room();
+        assert!(!collides(&m, 96.0, 96.0, 12.0, 64.0));
+        // la esquina de arriba a la izquierda entra en la pared (0,0)
+        assert!(collides(&m, 75.0, 75.0, 12.0, 64.0));
+        // justo apoyado sobre las dos paredes, sin entrar
+        assert!(!collides(&m, 76.0, 76.0, 12.0, 64.0));
+        // la de abajo a la derecha entra en la pared de la derecha
+        assert!(collides(&m, 245.0, 150.0, 12.0, 64.0));
+    }
+
+    #[test]
+    fn try_move_slides_along_walls() {
+        let m = room();
+        let mut p = Player::new(Vector2::new(128.0, 76.0)); // pegado a la pared de arriba
+        p.try_move(&m, Vector2::new(10.0, -10.0));
+        assert_eq!(p.pos, Vector2::new(138.0, 76.0));
+
+        // contra la esquina no avanza en ningún eje
+        let mut p = Player::new(Vector2::new(76.0, 76.0));
+        p.try_move(&m, Vector2::new(-5.0, -5.0));
+        assert_eq!(p.pos, Vector2::new(76.0, 76.0));
+    }
 }
@@ -0,0 +1,326 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::input::InputSnapshot;
+use crate::levels::{LevelMeta, LevelSource};
+use crate::maze::Maze;
+use crate::sim::{Sim, SimEvent};
+
+// Grabación de partidas: el nivel, su semilla y, por frame, el `dt` y el
+// `InputSnapshot` que recibió `Sim::step`. Como la simulación es pura,
+// volver a pasarle lo mismo da las mismas posiciones y eventos (las pistas
+// también: van en la entrada y la repetición las vuelve a mostrar).
+//
+// Formato de texto, una línea por frame. Los f32 van como hexadecimal de
+// `to_bits` para que vuelvan idénticos (un decimal podría redondear):
+//
+//   escape-replay 1
+//   level levels/01.txt          (o random:prim:14x10)
+//   seed 0
+//   screamers 1                  (0 si se jugó con el screamer apagado en las opciones)
+//   hints 0                      (pistas pedidas con las ayudas; 0 = sin ayuda de camino)
+//   f <dt> <forward> <strafe> <turn> <look> <sprint 0|1> <use 0|1> <pista 0|1>
+//   ...
+//   end won <x> <y> <ángulo> <tiempo>
+
+const MAGIC: &str = "escape-replay 1";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    pub dt: f32,
+    pub input: InputSnapshot,
+}
+
+/// Cómo terminó la partida
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Won,
+    Caught,
+    Quit,
+}
+
+impl Outcome {
+    fn name(self) -> &'static str {
+        match self {
+            Outcome::Won => "won",
+            Outcome::Caught => "caught",
+            Outcome::Quit => "quit",
+        }
+    }
+}
+
+/// Estado final grabado; `verify` lo compara bit a bit con la re-simulación
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ending {
+    pub outcome: Outcome,
+    pub x: f32,
+    pub y: f32,
+    pub angle: f32,
+    pub time: f32,
+}
+
+impl Ending {
+    pub fn of(sim: &Sim, outcome: Outcome) -> Self {
+        Self { outcome, x: sim.player.pos.x, y: sim.player.pos.y, angle: sim.player.a, time: sim.time }
+    }
+
+    fn same_bits(&self, other: &Ending) -> bool {
+        self.outcome == other.outcome
+            && [(self.x, other.x), (self.y, other.y), (self.angle, other.angle), (self.time, other.time)]
+                .iter()
+                .all(|(a, b)| a.to_bits() == b.to_bits())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Replay {
+    pub level: LevelSource,
+    pub screamers: bool, // false = la partida se jugó sin screamers (opción del usuario)
+    pub hints: u32,      // pistas hacia la puerta que pidió el jugador
+    pub frames: Vec<Frame>,
+    pub end: Option<Ending>,
+}
+
+impl Replay {
+    pub fn new(level: LevelSource, screamers: bool) -> Self {
+        Self { level, screamers, hints: 0, frames: Vec::new(), end: None }
+    }
+
+    /// Estado inicial de la partida: el del nivel más las opciones grabadas
+    pub fn new_sim(&self, maze: &Maze, meta: &LevelMeta) -> Sim {
+        let mut meta = meta.clone();
+        meta.screamer.enabled &= self.screamers;
+        Sim::new(maze, &meta)
+    }
+
+    pub fn push(&mut self, dt: f32, input: InputSnapshot) {
+        self.frames.push(Frame { dt, input });
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> std::io::Result<()> {
+        if let Some(dir) = path.as_ref().parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, self.to_text())
+    }
+
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, String> {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        Self::parse(&text).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut out = format!(
+            "{}\nlevel {}\nseed {}\nscreamers {}\nhints {}\n",
+            MAGIC, self.level.id(), self.level.seed(), self.screamers as u8, self.hints
+        );
+        for f in &self.frames {
+            let i = &f.input;
+            let _ = writeln!(
+                out,
+                "f {} {} {} {} {} {} {} {}",
+                hex(f.dt), hex(i.forward), hex(i.strafe), hex(i.turn), hex(i.look),
+                i.sprint as u8, i.use_pressed as u8, i.hint_pressed as u8
+            );
+        }
+        if let Some(e) = &self.end {
+            let _ = writeln!(out, "end {} {} {} {} {}", e.outcome.name(), hex(e.x), hex(e.y), hex(e.angle), hex(e.time));
+        }
+        out
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+        match lines.next() {
+            Some((_, l)) if l.trim() == MAGIC => {}
+            _ => return Err(format!("no es una repetición (falta '{}')", MAGIC)),
+        }
+
+        let mut level_id = None;
+        let mut seed = 0;
+        let mut screamers = true;
+        let mut hints = 0;
+        let mut frames = Vec::new();
+        let mut end = None;
+        for (n, line) in lines {
+            let err = |what: &str| format!("línea {}: {}", n + 1, what);
+            let mut words = line.split_whitespace();
+            match words.next() {
+                Some("level") => level_id = Some(line["level".len()..].trim().to_string()),
+                Some("seed") => seed = words.next().and_then(|s| s.parse().ok()).ok_or_else(|| err("semilla inválida"))?,
+                Some("screamers") => screamers = words.next() != Some("0"),
+                Some("hints") => hints = words.next().and_then(|s| s.parse().ok()).ok_or_else(|| err("cantidad de pistas inválida"))?,
+                Some("f") => {
+                    let mut w: Vec<&str> = words.collect();
+                    if w.len() == 7 {
+                        w.push("0"); // grabada antes de que las pistas fueran parte de la entrada
+                    }
+                    let [dt, forward, strafe, turn, look, sprint, use_pressed, hint_pressed] = w[..] else {
+                        return Err(err("se esperaban 8 valores"));
+                    };
+                    let num = |s| unhex(s).ok_or_else(|| err("número inválido"));
+                    let flag = |s| match s {
+                        "0" => Ok(false),
+                        "1" => Ok(true),
+                        _ => Err(err("se esperaba 0 o 1")),
+                    };
+                    let input = InputSnapshot {
+                        forward: num(forward)?,
+                        strafe: num(strafe)?,
+                        turn: num(turn)?,
+                        look: num(look)?,
+                        sprint: flag(sprint)?,
+                        use_pressed: flag(use_pressed)?,
+                        hint_pressed: flag(hint_pressed)?,
+                    };
+                    frames.push(Frame { dt: num(dt)?, input });
+                }
+                Some("end") => {
+                    let w: Vec<&str> = words.collect();
+                    let [outcome, x, y, angle, time] = w[..] else {
+                        return Err(err("se esperaban 5 valores"));
+                    };
+                    let outcome = [Outcome::Won, Outcome::Caught, Outcome::Quit]
+                        .into_iter()
+                        .find(|o| o.name() == outcome)
+                        .ok_or_else(|| err("resultado desconocido"))?;
+                    let num = |s| unhex(s).ok_or_else(|| err("número inválido"));
+                    end = Some(Ending { outcome, x: num(x)?, y: num(y)?, angle: num(angle)?, time: num(time)? });
+                }
+                _ => return Err(err("línea desconocida")),
+            }
+        }
+
+        let level_id = level_id.ok_or("falta la línea 'level'")?;
+        let level = LevelSource::from_id(&level_id, seed).ok_or(format!("nivel inválido: {}", level_id))?;
+        Ok(Self { level, screamers, hints, frames, end })
+    }
+
+    /// Vuelve a correr la partida sin ventana. Retorna el estado final y los
+    /// eventos con su número de frame; corta en el primer `Won`/`Caught`
+    /// igual que el juego.
+    pub fn run(&self) -> Result<(Ending, Vec<(usize, SimEvent)>), String> {
+        let (maze, meta) = self.level.load().map_err(|e| format!("{}: {}", self.level.id(), e))?;
+        let mut sim = self.new_sim(&maze, &meta);
+        let mut events = Vec::new();
+        for (n, f) in self.frames.iter().enumerate() {
+            for ev in sim.step(&maze, &f.input, f.dt) {
+                events.push((n, ev));
+                let outcome = match ev {
+                    SimEvent::Won => Outcome::Won,
+                    SimEvent::Caught(_) => Outcome::Caught,
+                    _ => continue,
+                };
+                return Ok((Ending::of(&sim, outcome), events));
+            }
+        }
+        Ok((Ending::of(&sim, Outcome::Quit), events))
+    }
+
+    /// Re-simula y compara con el final grabado. Sirve para confirmar que un
+    /// cambio en la física no altera partidas ya terminadas.
+    pub fn verify(&self) -> Result<Ending, String> {
+        let expected = self.end.ok_or("la repetición no tiene línea 'end'")?;
+        let (got, _) = self.run()?;
+        if got.same_bits(&expected) {
+            Ok(got)
+        } else {
+            Err(format!("la repetición diverge: se grabó {:?} y da {:?}", expected, got))
+        }
+    }
+}
+
+fn hex(v: f32) -> String {
+    format!("{:08x}", v.to_bits())
+}
+
+fn unhex(s: &str) -> Option<f32> {
+    u32::from_str_radix(s, 16).ok().map(f32::from_bits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::maze::generate::Algorithm;
+    use crate::sim::TICK;
+
+    fn random_level(seed: u64) -> LevelSource {
+        LevelSource::Random { seed, algorithm: Algorithm::ALL[0], cols: 8, rows: 6 }
+    }
+
+    // entrada "rara" a propósito: decimales que no son exactos en f32 y giros de mouse
+    fn scripted_input(n: usize) -> InputSnapshot {
+        InputSnapshot {
+            forward: if n % 90 < 70 { 1.0 } else { -0.37 },
+            strafe: ((n as f32) * 0.013).sin(),
+            turn: if n % 200 < 40 { 1.7 } else { 0.0 },
+            look: if n.is_multiple_of(7) { 0.0123 } else { 0.0 },
+            sprint: n % 300 > 150,
+            use_pressed: n.is_multiple_of(97),
+            hint_pressed: n % 150 == 20,
+        }
+    }
+
+    // graba como el juego: se corta en el primer Won/Caught
+    fn record(level: LevelSource, frames: usize) -> Replay {
+        let (maze, meta) = level.load().unwrap();
+        let mut rec = Replay::new(level, true);
+        let mut sim = rec.new_sim(&maze, &meta);
+        let mut outcome = Outcome::Quit;
+        'game: for n in 0..frames {
+            let input = scripted_input(n);
+            rec.push(TICK, input);
+            for ev in sim.step(&maze, &input, TICK) {
+                outcome = match ev {
+                    SimEvent::Won => Outcome::Won,
+                    SimEvent::Caught(_) => Outcome::Caught,
+                    _ => continue,
+                };
+                break 'game;
+            }
+        }
+        rec.end = Some(Ending::of(&sim, outcome));
+        rec
+    }
+
+    #[test]
+    fn text_round_trip_keeps_everything() {
+        let mut rec = record(random_level(4242), 600);
+        rec.hints = 3;
+        rec.screamers = false;
+        let back = Replay::parse(&rec.to_text()).unwrap();
+        assert_eq!(back, rec);
+        assert_eq!(back.level.seed(), 4242);
+        assert_eq!(back.hints, 3);
+    }
+
+    #[test]
+    fn old_replays_without_hints_parse_as_zero() {
+        let text = "escape-replay 1\nlevel random:prim:8x6\nseed 1\nscreamers 1\nf 3c088889 3f800000 0 0 0 0 1\n";
+        let rec = Replay::parse(text).unwrap();
+        assert_eq!(rec.hints, 0);
+        assert!(rec.frames[0].input.use_pressed && !rec.frames[0].input.hint_pressed);
+    }
+
+    #[test]
+    fn hints_are_replayed() {
+        let rec = record(random_level(31), 400);
+        let (_, events) = Replay::parse(&rec.to_text()).unwrap().run().unwrap();
+        let shown: Vec<usize> = events.iter().filter(|(_, e)| *e == SimEvent::Hint).map(|&(n, _)| n).collect();
+        assert_eq!(shown, [20, 170, 320]);
+    }
+
+    #[test]
+    fn recorded_run_verifies_bit_for_bit() {
+        for seed in [1, 77, 9001] {
+            let rec = record(random_level(seed), 2400);
+            let expected = rec.end.unwrap();
+            assert!(rec.verify().unwrap().same_bits(&expected));
+            // y también después de pasar por el texto
+            let back = Replay::parse(&rec.to_text()).unwrap();
+            assert!(back.verify().unwrap().same_bits(&expected));
+        }
+    }
+}
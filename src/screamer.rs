@@ -0,0 +1,208 @@
+use raylib::prelude::*;
+
+use crate::maze::generate::Rng;
+use crate::maze::{Cell, Connectivity, Maze};
+
+// IA del screamer (componente `ai` de su entidad): arranca quieto en su 'S',
+// patrulla alrededor, va a revisar donde escuchó pasos y persigue al jugador
+// mientras lo ve. Si lo pierde de vista un rato vuelve a investigar el último
+// lugar donde lo vio. La captura la resuelve el trigger de la entidad.
+// Todas las distancias de esta tabla están en celdas.
+
+const PATROL_SPEED: f32 = 1.0;      // celdas/s
+const INVESTIGATE_SPEED: f32 = 1.8;
+const CHASE_SPEED: f32 = 2.3;       // un poco más lento que el jugador (~2.65)
+pub const HEAR_RADIUS: f32 = 6.0;   // un paso a esta distancia o menos se escucha
+const SIGHT_RADIUS: f32 = 9.0;
+const LOSE_TIME: f32 = 1.5;         // segundos sin línea de vista antes de soltarlo
+const REPATH_TIME: f32 = 0.35;      // en persecución se recalcula el camino cada tanto
+const PATROL_RADIUS: isize = 6;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScreamerMode {
+    Idle,
+    Patrol,
+    Investigate,
+    Chase,
+}
+
+/// Cambios de estado que al juego le pueden interesar (sonidos, HUD)
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScreamerEvent {
+    Heard,
+    Spotted,
+    Lost,
+}
+
+pub struct Screamer {
+    pub mode: ScreamerMode,
+    home: Cell,
+    path: Vec<Cell>,    // celdas que faltan recorrer (la próxima al final)
+    wait: f32,          // Idle: cuánto falta para salir a patrullar
+    unseen: f32,        // Chase: segundos sin ver al jugador
+    repath: f32,
+    last_seen: Cell,
+    rng: Rng,
+}
+
+impl Screamer {
+    /// Quieto en `home` un par de segundos antes de salir a patrullar
+    pub fn new(home: Cell) -> Self {
+        Self {
+            mode: ScreamerMode::Idle,
+            home,
+            path: Vec::new(),
+            wait: 2.0,
+            unseen: 0.0,
+            repath: 0.0,
+            last_seen: home,
+            // misma semilla para la misma celda: la patrulla es reproducible
+            rng: Rng::new(((home.0 as u64) << 32) | home.1 as u64),
+        }
+    }
+
+    /// `footstep` = el jugador dio un paso este frame (el mismo que suena `step_snd`)
+    /// Mueve `pos` (la posición de la entidad) según el estado
+    pub fn update(
+        &mut self,
+        pos: &mut Vector2,
+        maze: &Maze,
+        player_pos: Vector2,
+        footstep: bool,
+        dt: f32,
+    ) -> Option<ScreamerEvent> {
+        let bs = maze.block_size() as f32;
+        let dist = (player_pos - *pos).length() / bs;
+        let player_cell = maze.world_to_cell(player_pos);
+        let sees = dist <= SIGHT_RADIUS && has_los(maze, *pos, player_pos);
+
+        let mut event = None;
+        match self.mode {
+            ScreamerMode::Chase => {
+                if sees {
+                    self.unseen = 0.0;
+                    self.last_seen = player_cell;
+                    self.repath -= dt;
+                    if self.repath <= 0.0 {
+                        self.repath = REPATH_TIME;
+                        self.go_to(*pos, maze, player_cell);
+                    }
+                } else {
+                    self.unseen += dt;
+                    if self.unseen >= LOSE_TIME {
+                        // lo perdió: va al último lugar donde lo vio
+                        self.mode = ScreamerMode::Investigate;
+                        let target = self.last_seen;
+                        self.go_to(*pos, maze, target);
+                        event = Some(ScreamerEvent::Lost);
+                    }
+                }
+            }
+            _ if sees => {
+                self.mode = ScreamerMode::Chase;
+                self.unseen = 0.0;
+                self.repath = REPATH_TIME;
+                self.last_seen = player_cell;
+                self.go_to(*pos, maze, player_cell);
+                event = Some(ScreamerEvent::Spotted);
+            }
+            _ if footstep && dist <= HEAR_RADIUS => {
+                self.mode = ScreamerMode::Investigate;
+                self.go_to(*pos, maze, player_cell);
+                event = Some(ScreamerEvent::Heard);
+            }
+            ScreamerMode::Idle => {
+                self.wait -= dt;
+                if self.wait <= 0.0 {
+                    self.pick_patrol_target(*pos, maze);
+                }
+            }
+            ScreamerMode::Patrol | ScreamerMode::Investigate => {}
+        }
+
+        let speed = match self.mode {
+            ScreamerMode::Idle => 0.0,
+            ScreamerMode::Patrol => PATROL_SPEED,
+            ScreamerMode::Investigate => INVESTIGATE_SPEED,
+            ScreamerMode::Chase => CHASE_SPEED,
+        };
+        let arrived = self.follow_path(pos, maze, speed * bs * dt);
+        if arrived && self.mode == ScreamerMode::Chase && sees && dist > 0.0 {
+            // misma celda que el jugador: el último tramo va directo
+            let to = player_pos - *pos;
+            *pos += to * (speed * bs * dt / to.length()).min(1.0);
+        }
+
+        // al llegar a destino (patrulla o lugar investigado) se queda mirando un rato
+        if arrived && matches!(self.mode, ScreamerMode::Patrol | ScreamerMode::Investigate) {
+            self.wait = if self.mode == ScreamerMode::Investigate { 2.5 } else { 1.0 + self.rng.below(3) as f32 };
+            self.mode = ScreamerMode::Idle;
+        }
+        event
+    }
+
+    fn go_to(&mut self, pos: Vector2, maze: &Maze, target: Cell) {
+        let from = maze.world_to_cell(pos);
+        self.path = maze.astar_path(from, target, Connectivity::Eight).unwrap_or_default();
+        self.path.reverse();
+        self.path.pop(); // la celda actual
+    }
+
+    // Un destino libre cerca de casa; si no encuentra, vuelve a casa
+    fn pick_patrol_target(&mut self, pos: Vector2, maze: &Maze) {
+        self.mode = ScreamerMode::Patrol;
+        for _ in 0..16 {
+            let span = (2 * PATROL_RADIUS + 1) as usize;
+            let c = (
+                self.home.0 - PATROL_RADIUS + self.rng.below(span) as isize,
+                self.home.1 - PATROL_RADIUS + self.rng.below(span) as isize,
+            );
+            if maze.is_walkable(c) && c != maze.world_to_cell(pos) {
+                self.go_to(pos, maze, c);
+                if !self.path.is_empty() {
+                    return;
+                }
+            }
+        }
+        let home = self.home;
+        self.go_to(pos, maze, home);
+    }
+
+    // Avanza `step` píxeles por el camino; true si se quedó sin camino
+    fn follow_path(&mut self, pos: &mut Vector2, maze: &Maze, mut step: f32) -> bool {
+        while let Some(&next) = self.path.last() {
+            let target = maze.cell_center_world(next);
+            let to = target - *pos;
+            let len = to.length();
+            if len <= step {
+                *pos = target;
+                step -= len;
+                self.path.pop();
+            } else {
+                *pos += to * (step / len);
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// Línea de vista simple: muestrea cada medio bloque entre `a` y `b`
+pub fn has_los(maze: &Maze, a: Vector2, b: Vector2) -> bool {
+    let bs = maze.block_size() as f32;
+    let dx = b.x - a.x;
+    let dy = b.y - a.y;
+    let steps = (dx.abs().max(dy.abs()) / (bs * 0.5)).max(1.0);
+    let sx = dx / steps;
+    let sy = dy / steps;
+
+    let mut x = a.x;
+    let mut y = a.y;
+    for _ in 0..steps as i32 {
+        let i = (x / bs) as isize;
+        let j = (y / bs) as isize;
+        if maze.is_blocking_at(i, j) { return false; }
+        x += sx; y += sy;
+    }
+    true
+}
@@ -0,0 +1,358 @@
+use crate::caster::DEFAULT_FOV;
+use crate::config;
+use crate::controls::Controls;
+use crate::minimap::{self, Corner, MapFog, MapMode, MapShape, MiniMapCfg};
+
+// Preferencias del usuario (video, audio, juego). Se guardan en `settings.cfg`
+// dentro de `config::config_dir` y se aplican al arrancar. La sensibilidad del
+// mouse vive en `Controls` (controls.cfg) pero también se edita desde acá.
+
+const FILE: &str = "settings.cfg";
+
+const RESOLUTIONS: [(i32, i32); 6] = [(800, 500), (1024, 640), (1280, 800), (1440, 900), (1600, 1000), (1920, 1080)];
+const FPS_CAPS: [u32; 6] = [30, 60, 120, 144, 240, 0]; // 0 = sin límite
+// campo de visión en grados: vale igual para el menú y para el archivo
+const FOV_MIN: f32 = 50.0;
+const FOV_MAX: f32 = 110.0;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Settings {
+    pub width: i32,
+    pub height: i32,
+    pub fullscreen: bool,
+    pub vsync: bool,
+    pub fps_cap: u32,      // 0 = sin límite
+    pub fov_deg: f32,
+    pub music_volume: f32, // 0..1
+    pub sfx_volume: f32,   // 0..1, multiplica el volumen propio de cada efecto
+    pub ui_volume: f32,    // 0..1, sonidos de los menús
+    pub screamer: bool,    // false = ningún nivel tiene screamer
+    pub assist: bool,      // migas en el piso y pistas hacia la puerta
+    pub minimap_fog: MapFog,
+    pub minimap_mode: MapMode,
+    pub minimap_shape: MapShape,
+    pub minimap_corner: Corner,
+    pub minimap_opacity: f32, // 0.2..1
+    pub minimap_zoom: f32,
+    pub minimap_enemies: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            width: 1024,
+            height: 640,
+            fullscreen: false,
+            vsync: false,
+            fps_cap: 120,
+            fov_deg: DEFAULT_FOV.to_degrees().round(),
+            music_volume: 0.60,
+            sfx_volume: 1.0,
+            ui_volume: 0.8,
+            screamer: true,
+            assist: false,
+            minimap_fog: MapFog::Remembered,
+            minimap_mode: MapMode::NorthUp,
+            minimap_shape: MapShape::Square,
+            minimap_corner: Corner::TopLeft,
+            minimap_opacity: 0.85,
+            minimap_zoom: 1.0,
+            minimap_enemies: true,
+        }
+    }
+}
+
+/// Filas de la pantalla de opciones, en orden
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SettingRow {
+    Resolution,
+    Fullscreen,
+    Vsync,
+    FpsCap,
+    Fov,
+    MusicVolume,
+    SfxVolume,
+    UiVolume,
+    MouseSensitivity,
+    Screamer,
+    Assist,
+    MinimapFog,
+    MinimapMode,
+    MinimapShape,
+    MinimapCorner,
+    MinimapOpacity,
+    MinimapZoom,
+    MinimapEnemies,
+}
+
+impl SettingRow {
+    pub const ALL: [SettingRow; 18] = [
+        SettingRow::Resolution,
+        SettingRow::Fullscreen,
+        SettingRow::Vsync,
+        SettingRow::FpsCap,
+        SettingRow::Fov,
+        SettingRow::MusicVolume,
+        SettingRow::SfxVolume,
+        SettingRow::UiVolume,
+        SettingRow::MouseSensitivity,
+        SettingRow::Screamer,
+        SettingRow::Assist,
+        SettingRow::MinimapFog,
+        SettingRow::MinimapMode,
+        SettingRow::MinimapShape,
+        SettingRow::MinimapCorner,
+        SettingRow::MinimapOpacity,
+        SettingRow::MinimapZoom,
+        SettingRow::MinimapEnemies,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            SettingRow::Resolution => "Resolución",
+            SettingRow::Fullscreen => "Pantalla completa",
+            SettingRow::Vsync => "VSync",
+            SettingRow::FpsCap => "Límite de FPS",
+            SettingRow::Fov => "Campo de visión",
+            SettingRow::MusicVolume => "Volumen de la música",
+            SettingRow::SfxVolume => "Volumen de los efectos",
+            SettingRow::UiVolume => "Volumen de los menús",
+            SettingRow::MouseSensitivity => "Sensibilidad del mouse",
+            SettingRow::Screamer => "Screamer",
+            SettingRow::Assist => "Ayudas para orientarse",
+            SettingRow::MinimapFog => "Niebla del minimapa",
+            SettingRow::MinimapMode => "Minimapa",
+            SettingRow::MinimapShape => "Forma del minimapa",
+            SettingRow::MinimapCorner => "Esquina del minimapa",
+            SettingRow::MinimapOpacity => "Opacidad del minimapa",
+            SettingRow::MinimapZoom => "Zoom del minimapa",
+            SettingRow::MinimapEnemies => "Enemigos en el minimapa",
+        }
+    }
+}
+
+impl Settings {
+    /// FOV horizontal en radianes, para el caster
+    pub fn fov(&self) -> f32 {
+        self.fov_deg.to_radians()
+    }
+
+    /// Valor de una fila como se muestra en pantalla
+    pub fn value_text(&self, row: SettingRow, controls: &Controls) -> String {
+        let yes_no = |v: bool| if v { "sí" } else { "no" }.to_string();
+        match row {
+            SettingRow::Resolution => format!("{}x{}", self.width, self.height),
+            SettingRow::Fullscreen => yes_no(self.fullscreen),
+            SettingRow::Vsync => yes_no(self.vsync),
+            SettingRow::FpsCap if self.fps_cap == 0 => "sin límite".to_string(),
+            SettingRow::FpsCap => self.fps_cap.to_string(),
+            SettingRow::Fov => format!("{:.0}°", self.fov_deg),
+            SettingRow::MusicVolume => format!("{:.0}%", self.music_volume * 100.0),
+            SettingRow::SfxVolume => format!("{:.0}%", self.sfx_volume * 100.0),
+            SettingRow::UiVolume => format!("{:.0}%", self.ui_volume * 100.0),
+            SettingRow::MouseSensitivity => format!("{:.4}", controls.mouse_sensitivity),
+            SettingRow::Screamer => yes_no(self.screamer),
+            SettingRow::Assist => yes_no(self.assist),
+            SettingRow::MinimapFog => self.minimap_fog.label().to_string(),
+            SettingRow::MinimapMode => self.minimap_mode.label().to_string(),
+            SettingRow::MinimapShape => self.minimap_shape.label().to_string(),
+            SettingRow::MinimapCorner => self.minimap_corner.label().to_string(),
+            SettingRow::MinimapOpacity => format!("{:.0}%", self.minimap_opacity * 100.0),
+            SettingRow::MinimapZoom => format!("x{:.2}", self.minimap_zoom),
+            SettingRow::MinimapEnemies => yes_no(self.minimap_enemies),
+        }
+    }
+
+    /// Izquierda/derecha sobre una fila (`dir` = -1 o +1)
+    pub fn adjust(&mut self, row: SettingRow, dir: i32, controls: &mut Controls) {
+        let step = dir as f32;
+        match row {
+            SettingRow::Resolution => {
+                let i = cycle(RESOLUTIONS.iter().position(|&r| r == (self.width, self.height)), RESOLUTIONS.len(), dir);
+                (self.width, self.height) = RESOLUTIONS[i];
+            }
+            SettingRow::Fullscreen => self.fullscreen = !self.fullscreen,
+            SettingRow::Vsync => self.vsync = !self.vsync,
+            SettingRow::FpsCap => {
+                self.fps_cap = FPS_CAPS[cycle(FPS_CAPS.iter().position(|&f| f == self.fps_cap), FPS_CAPS.len(), dir)];
+            }
+            SettingRow::Fov => self.fov_deg = (self.fov_deg + step * 5.0).clamp(FOV_MIN, FOV_MAX),
+            SettingRow::MusicVolume => self.music_volume = (self.music_volume + step * 0.1).clamp(0.0, 1.0),
+            SettingRow::SfxVolume => self.sfx_volume = (self.sfx_volume + step * 0.1).clamp(0.0, 1.0),
+            SettingRow::UiVolume => self.ui_volume = (self.ui_volume + step * 0.1).clamp(0.0, 1.0),
+            SettingRow::MouseSensitivity => {
+                controls.mouse_sensitivity = (controls.mouse_sensitivity + step * 0.0004).clamp(0.0004, 0.02);
+            }
+            SettingRow::Screamer => self.screamer = !self.screamer,
+            SettingRow::Assist => self.assist = !self.assist,
+            SettingRow::MinimapFog => {
+                self.minimap_fog = MapFog::ALL[cycle(MapFog::ALL.iter().position(|&f| f == self.minimap_fog), MapFog::ALL.len(), dir)];
+            }
+            SettingRow::MinimapMode => {
+                self.minimap_mode = MapMode::ALL[cycle(MapMode::ALL.iter().position(|&m| m == self.minimap_mode), MapMode::ALL.len(), dir)];
+            }
+            SettingRow::MinimapShape => {
+                self.minimap_shape = MapShape::ALL[cycle(MapShape::ALL.iter().position(|&s| s == self.minimap_shape), MapShape::ALL.len(), dir)];
+            }
+            SettingRow::MinimapCorner => {
+                self.minimap_corner = Corner::ALL[cycle(Corner::ALL.iter().position(|&c| c == self.minimap_corner), Corner::ALL.len(), dir)];
+            }
+            SettingRow::MinimapOpacity => self.minimap_opacity = (self.minimap_opacity + step * 0.1).clamp(0.2, 1.0),
+            SettingRow::MinimapZoom => self.minimap_zoom = minimap::zoom_step(self.minimap_zoom, dir),
+            SettingRow::MinimapEnemies => self.minimap_enemies = !self.minimap_enemies,
+        }
+    }
+
+    /// Minimapa para una pantalla de `screen_h` píxeles de alto
+    pub fn minimap(&self, screen_h: i32) -> MiniMapCfg {
+        MiniMapCfg {
+            tile_px: (screen_h / 100).max(4),
+            margin: 8,
+            size: screen_h * 3 / 10,
+            scale: self.minimap_zoom,
+            mode: self.minimap_mode,
+            shape: self.minimap_shape,
+            corner: self.minimap_corner,
+            opacity: self.minimap_opacity,
+            fog: self.minimap_fog,
+            enemies: self.minimap_enemies,
+        }
+    }
+
+    /// Lee `settings.cfg`; lo que falte o no se entienda queda por defecto
+    pub fn load() -> Self {
+        config::load(FILE, Self::from_kv)
+    }
+
+    pub fn save(&self) -> std::io::Result<()> {
+        config::save(FILE, "preferencias (fps_cap = 0: sin límite)", &self.to_kv())
+    }
+
+    pub fn from_kv(entries: &[(String, String)], origin: &str) -> Self {
+        let mut s = Self::default();
+        for (k, v) in entries {
+            let ok = match k.as_str() {
+                "resolution" => v
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.trim().parse().ok()?, h.trim().parse().ok()?)))
+                    .filter(|&(w, h): &(i32, i32)| w >= 320 && h >= 200)
+                    .map(|(w, h)| (s.width, s.height) = (w, h))
+                    .is_some(),
+                "fullscreen" => config::parse_bool(v).map(|x| s.fullscreen = x).is_some(),
+                "vsync" => config::parse_bool(v).map(|x| s.vsync = x).is_some(),
+                "fps_cap" => v.parse().map(|x| s.fps_cap = x).is_ok(),
+                "fov" => v.parse::<f32>().map(|x| s.fov_deg = x.clamp(FOV_MIN, FOV_MAX)).is_ok(),
+                "music_volume" => v.parse::<f32>().map(|x| s.music_volume = x.clamp(0.0, 1.0)).is_ok(),
+                "sfx_volume" => v.parse::<f32>().map(|x| s.sfx_volume = x.clamp(0.0, 1.0)).is_ok(),
+                "ui_volume" => v.parse::<f32>().map(|x| s.ui_volume = x.clamp(0.0, 1.0)).is_ok(),
+                "screamer" => config::parse_bool(v).map(|x| s.screamer = x).is_some(),
+                "assist" => config::parse_bool(v).map(|x| s.assist = x).is_some(),
+                "minimap_fog" => MapFog::ALL.into_iter().find(|f| f.name() == v).map(|f| s.minimap_fog = f).is_some(),
+                "minimap_mode" => MapMode::ALL.into_iter().find(|m| m.name() == v).map(|m| s.minimap_mode = m).is_some(),
+                "minimap_shape" => MapShape::ALL.into_iter().find(|m| m.name() == v).map(|m| s.minimap_shape = m).is_some(),
+                "minimap_corner" => Corner::ALL.into_iter().find(|c| c.name() == v).map(|c| s.minimap_corner = c).is_some(),
+                "minimap_opacity" => v.parse::<f32>().map(|x| s.minimap_opacity = x.clamp(0.2, 1.0)).is_ok(),
+                "minimap_enemies" => config::parse_bool(v).map(|x| s.minimap_enemies = x).is_some(),
+                "minimap_zoom" => v.parse::<f32>().map(|x| s.minimap_zoom = x.clamp(minimap::ZOOM_MIN, minimap::ZOOM_MAX)).is_ok(),
+                _ => {
+                    eprintln!("{}: clave desconocida '{}'", origin, k);
+                    continue;
+                }
+            };
+            if !ok {
+                eprintln!("{}: valor inválido para {}: '{}'", origin, k, v);
+            }
+        }
+        s
+    }
+
+    pub fn to_kv(&self) -> Vec<(String, String)> {
+        vec![
+            ("resolution".into(), format!("{}x{}", self.width, self.height)),
+            ("fullscreen".into(), self.fullscreen.to_string()),
+            ("vsync".into(), self.vsync.to_string()),
+            ("fps_cap".into(), self.fps_cap.to_string()),
+            ("fov".into(), self.fov_deg.to_string()),
+            ("music_volume".into(), self.music_volume.to_string()),
+            ("sfx_volume".into(), self.sfx_volume.to_string()),
+            ("ui_volume".into(), self.ui_volume.to_string()),
+            ("screamer".into(), self.screamer.to_string()),
+            ("assist".into(), self.assist.to_string()),
+            ("minimap_fog".into(), self.minimap_fog.name().to_string()),
+            ("minimap_mode".into(), self.minimap_mode.name().to_string()),
+            ("minimap_shape".into(), self.minimap_shape.name().to_string()),
+            ("minimap_corner".into(), self.minimap_corner.name().to_string()),
+            ("minimap_opacity".into(), self.minimap_opacity.to_string()),
+            ("minimap_zoom".into(), self.minimap_zoom.to_string()),
+            ("minimap_enemies".into(), self.minimap_enemies.to_string()),
+        ]
+    }
+}
+
+// Siguiente/anterior de una lista circular; un valor que no está en la lista arranca del principio
+fn cycle(current: Option<usize>, len: usize, dir: i32) -> usize {
+    match current {
+        Some(i) => (i as i32 + dir).rem_euclid(len as i32) as usize,
+        None => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kv(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|&(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn kv_round_trip() {
+        let s = Settings {
+            width: 1440,
+            height: 900,
+            fullscreen: true,
+            fps_cap: 0,
+            fov_deg: 95.0,
+            music_volume: 0.3,
+            screamer: false,
+            assist: true,
+            minimap_fog: MapFog::Off,
+            minimap_mode: MapMode::Rotating,
+            minimap_shape: MapShape::Circle,
+            minimap_corner: Corner::BottomRight,
+            minimap_opacity: 0.5,
+            minimap_zoom: 2.0,
+            minimap_enemies: false,
+            ..Settings::default()
+        };
+        assert_eq!(Settings::from_kv(&s.to_kv(), "test"), s);
+    }
+
+    #[test]
+    fn bad_values_fall_back_to_defaults() {
+        let d = Settings::default();
+        let s = Settings::from_kv(
+            &kv(&[
+                ("resolution", "10x10"),
+                ("fullscreen", "quizás"),
+                ("fps_cap", "-5"),
+                ("fov", "mucho"),
+                ("minimap_shape", "hexágono"),
+                ("no_existe", "1"),
+            ]),
+            "test",
+        );
+        assert_eq!(s, d);
+    }
+
+    #[test]
+    fn fov_uses_the_same_range_in_file_and_menu() {
+        let mut s = Settings::from_kv(&kv(&[("fov", "130")]), "test");
+        assert_eq!(s.fov_deg, FOV_MAX);
+        s.adjust(SettingRow::Fov, -1, &mut Controls::default());
+        assert_eq!(s.fov_deg, FOV_MAX - 5.0);
+
+        let s = Settings::from_kv(&kv(&[("fov", "10")]), "test");
+        assert_eq!(s.fov_deg, FOV_MIN);
+    }
+}
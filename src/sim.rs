@@ -0,0 +1,253 @@
+use crate::assist::{Hint, Trail};
+use crate::entity::{Action, World, WorldEvent};
+use crate::input::InputSnapshot;
+use crate::levels::LevelMeta;
+use crate::maze::Maze;
+use crate::player::{spawn_player_from_maze, Player};
+use crate::screamer::ScreamerEvent;
+
+// Lógica del juego sin ventana: jugador, entidades, pasos y reloj del nivel.
+// Entra un `InputSnapshot` y un `dt`, salen eventos; sonidos, pantallas y
+// dibujo quedan del lado de main.rs. Mismo estado + misma entrada = mismo resultado.
+//
+// El juego avanza siempre de a `TICK` (ver `FixedStep`): el resultado no
+// depende de los FPS y un tirón no hace atravesar paredes finas. El dibujo
+// interpola entre los dos últimos ticks con `alpha`.
+
+/// Paso fijo de la simulación (120 Hz)
+pub const TICK: f32 = 1.0 / 120.0;
+
+// tope de ticks por frame: tras un tirón largo se pierde tiempo en vez de
+// encadenar cientos de ticks (y hacer el frame siguiente más lento todavía)
+const MAX_TICKS_PER_FRAME: u32 = 8;
+
+/// Distancia entre pasos (~medio tile si tile=64)
+pub const STEP_PIXELS: f32 = 34.0;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SimEvent {
+    Footstep,
+    EntityStep(usize), // paso de una entidad (índice en `world.entities`)
+    Ai(usize, ScreamerEvent),
+    Won,
+    Caught(usize), // la entidad que atrapó al jugador
+    Hint,          // se mostró una pista (`Sim::hint`)
+}
+
+/// Acumulador de tiempo real: cuántos ticks corresponden a cada frame
+#[derive(Default)]
+pub struct FixedStep {
+    accum: f32,
+    pending: InputSnapshot, // mouse y "usar" de frames que no llegaron a un tick
+}
+
+impl FixedStep {
+    /// Suma el tiempo del frame y retorna la entrada de cada tick a correr (puede no haber ninguno).
+    /// Lo continuo (ejes, sprint) vale para todos; el giro del mouse, "usar"
+    /// y la pista se entregan una sola vez, en el primer tick.
+    pub fn advance(&mut self, frame_dt: f32, input: &InputSnapshot) -> Vec<InputSnapshot> {
+        self.pending = InputSnapshot {
+            look: self.pending.look + input.look,
+            use_pressed: self.pending.use_pressed || input.use_pressed,
+            hint_pressed: self.pending.hint_pressed || input.hint_pressed,
+            ..*input
+        };
+        let n = self.ticks(frame_dt);
+        let mut out = Vec::with_capacity(n as usize);
+        for _ in 0..n {
+            out.push(self.pending);
+            self.pending.look = 0.0;
+            self.pending.use_pressed = false;
+            self.pending.hint_pressed = false;
+        }
+        out
+    }
+
+    /// Descarta el tiempo acumulado y la entrada pendiente (pausa, fin o cambio de partida)
+    pub fn clear(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Solo el reloj: cuántos ticks enteros entran sumando `frame_dt`
+    pub fn ticks(&mut self, frame_dt: f32) -> u32 {
+        self.accum = (self.accum + frame_dt).min(TICK * MAX_TICKS_PER_FRAME as f32);
+        let mut n = 0;
+        while self.accum >= TICK {
+            self.accum -= TICK;
+            n += 1;
+        }
+        n
+    }
+
+    /// Fracción (0..1) del próximo tick ya transcurrida: cuánto interpolar al dibujar
+    pub fn alpha(&self) -> f32 {
+        (self.accum / TICK).clamp(0.0, 1.0)
+    }
+}
+
+pub struct Sim {
+    pub player: Player,
+    prev_player: Player, // al empezar el último tick
+    pub world: World,
+    pub trail: Trail, // por dónde pasó el jugador (se dibuja solo con las ayudas)
+    pub hint: Option<Hint>, // última pista pedida (con `hint_pressed`); no cambia la física
+    pub time: f32,    // tiempo jugado en el nivel (se compara con @par)
+    step_accum: f32,  // píxeles caminados desde el último paso
+}
+
+impl Sim {
+    /// Estado inicial del nivel: jugador en la 'P' y entidades desde los tiles
+    pub fn new(maze: &Maze, meta: &LevelMeta) -> Self {
+        let player = spawn_player_from_maze(maze);
+        Self {
+            prev_player: player.clone(),
+            player,
+            world: World::spawn_from_maze(maze, meta),
+            trail: Trail::default(),
+            hint: None,
+            time: 0.0,
+            step_accum: 0.0,
+        }
+    }
+
+    pub fn step(&mut self, maze: &Maze, input: &InputSnapshot, dt: f32) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+        self.time += dt;
+
+        self.prev_player = self.player.clone();
+        self.player.update(input, maze, dt);
+        self.trail.drop_at(maze, self.player.pos);
+        if input.hint_pressed && let Some(hint) = Hint::to_exit(maze, self.player.pos, self.time) {
+            self.hint = Some(hint);
+            events.push(SimEvent::Hint);
+        }
+
+        // pasos por distancia recorrida (el screamer también los escucha)
+        let mut footstep = false;
+        self.step_accum += (self.player.pos - self.prev_player.pos).length();
+        if self.step_accum >= STEP_PIXELS {
+            self.step_accum -= STEP_PIXELS;
+            footstep = true;
+            events.push(SimEvent::Footstep);
+        }
+
+        let use_point = input.use_pressed.then(|| self.player.use_point(maze));
+        for ev in self.world.update(maze, self.player.pos, use_point, footstep, dt) {
+            events.push(match ev {
+                WorldEvent::Ai(idx, e) => SimEvent::Ai(idx, e),
+                WorldEvent::Step(idx) => SimEvent::EntityStep(idx),
+                WorldEvent::Triggered(_, Action::Win) => SimEvent::Won,
+                WorldEvent::Triggered(idx, Action::Catch) => SimEvent::Caught(idx),
+            });
+        }
+        events
+    }
+
+    /// Jugador para dibujar, interpolado entre el tick anterior y el actual
+    pub fn player_view(&self, alpha: f32) -> Player {
+        let mut p = self.player.clone();
+        let prev = &self.prev_player;
+        p.pos = prev.pos + (self.player.pos - prev.pos) * alpha;
+        // el ángulo puede haber cruzado ±π: se interpola por el lado corto
+        let mut da = self.player.a - prev.a;
+        if da > std::f32::consts::PI { da -= 2.0 * std::f32::consts::PI; }
+        if da < -std::f32::consts::PI { da += 2.0 * std::f32::consts::PI; }
+        p.a = prev.a + da * alpha;
+        p
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entity::EntityKind;
+
+    fn level(rows: &[&str]) -> (Maze, LevelMeta) {
+        (Maze::parse(&rows.join("\n"), 64), LevelMeta::with_name("test"))
+    }
+
+    // corre `secs` segundos con la misma entrada y junta los eventos
+    fn run(sim: &mut Sim, maze: &Maze, input: &InputSnapshot, secs: f32) -> Vec<SimEvent> {
+        let mut events = Vec::new();
+        for _ in 0..(secs / TICK) as usize {
+            events.extend(sim.step(maze, input, TICK));
+        }
+        events
+    }
+
+    const FORWARD: InputSnapshot = InputSnapshot {
+        forward: 1.0, strafe: 0.0, turn: 0.0, look: 0.0, sprint: false, use_pressed: false, hint_pressed: false,
+    };
+
+    #[test]
+    fn walking_into_a_wall_slides_along_it() {
+        let (maze, meta) = level(&["######", "#P   #", "######"]);
+        let mut sim = Sim::new(&maze, &meta);
+        sim.player.a = 0.3; // hacia el este, un poco hacia la pared de abajo
+        let events = run(&mut sim, &maze, &FORWARD, 0.5);
+
+        // la pared de abajo frena el eje y; el x sigue avanzando
+        let p = sim.player.pos;
+        assert!(p.y < 128.0 - 12.0 && p.y > 96.0, "{:?}", p);
+        assert!(p.x > 96.0 + 64.0, "{:?}", p);
+        assert!(events.contains(&SimEvent::Footstep));
+
+        // y nunca atraviesa la del fondo
+        run(&mut sim, &maze, &FORWARD, 3.0);
+        assert!(sim.player.pos.x <= 5.0 * 64.0 - 12.0);
+    }
+
+    #[test]
+    fn using_the_door_wins() {
+        let (maze, meta) = level(&["#####", "#P D#", "#####"]);
+        let mut sim = Sim::new(&maze, &meta);
+        let events = run(&mut sim, &maze, &FORWARD, 1.0);
+        assert!(!events.contains(&SimEvent::Won), "para ganar hay que usar la puerta");
+
+        let use_door = InputSnapshot { use_pressed: true, ..InputSnapshot::default() };
+        assert!(sim.step(&maze, &use_door, TICK).contains(&SimEvent::Won));
+    }
+
+    #[test]
+    fn screamer_in_range_catches() {
+        let (maze, meta) = level(&["########", "#P    S#", "########"]);
+        let mut sim = Sim::new(&maze, &meta);
+        let idx = sim.world.entities.iter().position(|e| e.kind == EntityKind::Screamer).unwrap();
+        let events = run(&mut sim, &maze, &FORWARD, 3.0);
+        assert!(events.contains(&SimEvent::Caught(idx)), "{:?}", events);
+    }
+
+    #[test]
+    fn disabled_screamer_never_catches() {
+        let (maze, mut meta) = level(&["########", "#P    S#", "########"]);
+        meta.screamer.enabled = false;
+        let mut sim = Sim::new(&maze, &meta);
+        let events = run(&mut sim, &maze, &FORWARD, 3.0);
+        assert!(!events.iter().any(|e| matches!(e, SimEvent::Caught(_))));
+    }
+
+    #[test]
+    fn hint_press_shows_the_path_once() {
+        let (maze, meta) = level(&["######", "#P  D#", "######"]);
+        let mut sim = Sim::new(&maze, &meta);
+        assert!(run(&mut sim, &maze, &FORWARD, 0.2).iter().all(|e| *e != SimEvent::Hint));
+        assert!(sim.hint.is_none());
+
+        let ask = InputSnapshot { hint_pressed: true, ..InputSnapshot::default() };
+        assert!(sim.step(&maze, &ask, TICK).contains(&SimEvent::Hint));
+        let hint = sim.hint.as_ref().unwrap();
+        assert_eq!(hint.path().last(), Some(&maze.cell_center_world((4, 1))));
+        assert_eq!(hint.alpha(sim.time), 1.0);
+    }
+
+    #[test]
+    fn cleared_clock_drops_pending_use() {
+        let mut clock = FixedStep::default();
+        let press = InputSnapshot { use_pressed: true, look: 0.5, ..InputSnapshot::default() };
+        assert!(clock.advance(TICK * 0.5, &press).is_empty()); // todavía no llega a un tick
+        clock.clear();
+        let ticks = clock.advance(TICK, &InputSnapshot::default());
+        assert_eq!(ticks.len(), 1);
+        assert!(!ticks[0].use_pressed && ticks[0].look == 0.0);
+    }
+}
@@ -0,0 +1,68 @@
+use raylib::prelude::*;
+
+use crate::entity::EntityKind;
+
+/// Tipos de celda del mapa. Todo lo que significa cada uno está en `DEFS`;
+/// para agregar un tile nuevo basta con sumar una variante y su fila en la tabla.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum Tile {
+    Empty,
+    Wall,
+    Door,
+    Spawn,
+    Screamer,
+    OpenSky,
+    Item,
+}
+
+/// Textura con la que se dibujan las caras de un tile sólido
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum WallTex {
+    Wall,
+    Door,
+}
+
+pub struct TileDef {
+    pub tile: Tile,
+    pub ch: char,
+    pub solid: bool,              // bloquea movimiento, rayos y línea de vista
+    pub texture: Option<WallTex>, // solo los sólidos
+    pub minimap: Color,
+    pub ceiling: bool,            // false = se ve el cielo
+    pub spawn: bool,              // inicio del jugador
+    pub exit: bool,               // se "usa" con E para ganar
+    pub entity: Option<EntityKind>, // entidad que aparece en esta celda al cargar el mapa
+}
+
+// En el mismo orden que las variantes de `Tile`
+static DEFS: [TileDef; 7] = [
+    TileDef { tile: Tile::Empty, ch: ' ', solid: false, texture: None, minimap: Color::BLACK,
+              ceiling: true, spawn: false, exit: false, entity: None },
+    TileDef { tile: Tile::Wall, ch: '#', solid: true, texture: Some(WallTex::Wall), minimap: Color::DARKGRAY,
+              ceiling: true, spawn: false, exit: false, entity: None },
+    TileDef { tile: Tile::Door, ch: 'D', solid: true, texture: Some(WallTex::Door), minimap: Color::GOLD,
+              ceiling: true, spawn: false, exit: true, entity: Some(EntityKind::Exit) },
+    TileDef { tile: Tile::Spawn, ch: 'P', solid: false, texture: None, minimap: Color::DARKBLUE,
+              ceiling: true, spawn: true, exit: false, entity: Some(EntityKind::Start) },
+    TileDef { tile: Tile::Screamer, ch: 'S', solid: false, texture: None, minimap: Color::MAROON,
+              ceiling: true, spawn: false, exit: false, entity: Some(EntityKind::Screamer) },
+    TileDef { tile: Tile::OpenSky, ch: '.', solid: false, texture: None, minimap: Color::new(20, 26, 48, 255),
+              ceiling: false, spawn: false, exit: false, entity: None },
+    TileDef { tile: Tile::Item, ch: 'I', solid: false, texture: None, minimap: Color::BLACK,
+              ceiling: true, spawn: false, exit: false, entity: Some(EntityKind::Item) },
+];
+
+impl Tile {
+    /// None si el carácter no corresponde a ningún tile
+    pub fn from_char(ch: char) -> Option<Tile> {
+        DEFS.iter().find(|d| d.ch == ch).map(|d| d.tile)
+    }
+
+    pub fn def(self) -> &'static TileDef {
+        &DEFS[self as usize]
+    }
+
+    pub fn is_solid(self) -> bool {
+        self.def().solid
+    }
+}
@@ -0,0 +1,220 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::levels::parse_header;
+use crate::maze::{is_box_drawing, Connectivity, Maze};
+use crate::tile::Tile;
+
+// Chequeos de niveles. `check_maze` mira la estructura (spawn, puertas
+// alcanzables) y `check_grid_text` el texto crudo, que se pierde al parsear
+// (filas disparejas y caracteres desconocidos se rellenan con piso). Esos dos
+// son errores: el juego los tolera, pero en el repo no entran mapas rotos.
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+pub struct Issue {
+    pub severity: Severity,
+    pub msg: String,
+}
+
+impl Issue {
+    fn error(msg: String) -> Self {
+        Self { severity: Severity::Error, msg }
+    }
+
+    fn warning(msg: String) -> Self {
+        Self { severity: Severity::Warning, msg }
+    }
+}
+
+impl fmt::Display for Issue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.severity {
+            Severity::Error => write!(f, "error: {}", self.msg),
+            Severity::Warning => write!(f, "aviso: {}", self.msg),
+        }
+    }
+}
+
+pub fn has_errors(issues: &[Issue]) -> bool {
+    issues.iter().any(|i| i.severity == Severity::Error)
+}
+
+/// Spawn único y fuera de paredes, y al menos una puerta con una cara alcanzable
+pub fn check_maze(maze: &Maze) -> Vec<Issue> {
+    let mut issues = Vec::new();
+
+    let spawns = cells_where(maze, |t| t.def().spawn);
+    let start = match spawns.as_slice() {
+        [] => {
+            // spawn_player_from_maze cae en (1,1)
+            issues.push(Issue::error("falta 'P' (el jugador aparece en la celda (1,1))".to_string()));
+            if maze.is_blocking_at(1, 1) {
+                issues.push(Issue::error("el spawn por defecto (1,1) está dentro de una pared".to_string()));
+                None
+            } else {
+                Some((1, 1))
+            }
+        }
+        [only] => Some(*only),
+        [first, rest @ ..] => {
+            let others: Vec<String> = rest.iter().map(|c| format!("{:?}", c)).collect();
+            issues.push(Issue::error(format!(
+                "hay {} 'P': se usa {:?} y se ignoran {}", spawns.len(), first, others.join(", ")
+            )));
+            Some(*first)
+        }
+    };
+
+    let doors = cells_where(maze, |t| t.def().exit);
+    if doors.is_empty() {
+        issues.push(Issue::error("no hay puerta 'D'".to_string()));
+        return issues;
+    }
+
+    let Some(start) = start else { return issues; };
+
+    // la puerta es sólida: bfs_path la acepta como meta si hay una cara libre
+    let (ok, unreachable): (Vec<_>, Vec<_>) = doors
+        .into_iter()
+        .partition(|&door| maze.bfs_path(start, door, Connectivity::Four).is_some());
+
+    if ok.is_empty() {
+        issues.push(Issue::error(format!("ninguna puerta es alcanzable desde {:?}", start)));
+    } else {
+        for door in unreachable {
+            issues.push(Issue::warning(format!("la puerta {:?} no es alcanzable", door)));
+        }
+    }
+    issues
+}
+
+/// Filas de distinto ancho y caracteres que no son ningún tile.
+/// `first_line` es el número de línea (desde 1) de la primera fila del grid.
+pub fn check_grid_text(grid: &str, first_line: usize) -> Vec<Issue> {
+    let mut issues = Vec::new();
+    if is_box_drawing(grid) {
+        return issues; // el formato "+--+" tiene sus propias reglas y es tolerante
+    }
+
+    let rows: Vec<(usize, &str)> = grid
+        .lines()
+        .enumerate()
+        .map(|(n, l)| (n + first_line, l))
+        .filter(|(_, l)| !l.is_empty())
+        .collect();
+    let widest = rows.iter().map(|(_, l)| l.chars().count()).max().unwrap_or(0);
+
+    for &(line, text) in &rows {
+        let w = text.chars().count();
+        if w < widest {
+            issues.push(Issue::error(format!(
+                "línea {}: {} columnas en vez de {} (se rellena con piso)", line, w, widest
+            )));
+        }
+        for (col, ch) in text.chars().enumerate() {
+            if Tile::from_char(ch).is_none() {
+                issues.push(Issue::error(format!(
+                    "línea {}, columna {}: carácter desconocido {:?} (se toma como piso)", line, col + 1, ch
+                )));
+            }
+        }
+    }
+    issues
+}
+
+/// Todos los chequeos sobre un archivo de nivel (cabecera `@` incluida)
+pub fn check_level_file<P: AsRef<Path>>(path: P) -> std::io::Result<Vec<Issue>> {
+    let text = fs::read_to_string(path)?;
+    let (_, grid, header_lines) = parse_header(&text, "nivel");
+
+    let mut issues = check_grid_text(&grid, header_lines + 1);
+    issues.extend(check_maze(&Maze::parse(&grid, 64)));
+    Ok(issues)
+}
+
+fn cells_where(maze: &Maze, pred: impl Fn(Tile) -> bool) -> Vec<(isize, isize)> {
+    let mut out = Vec::new();
+    for j in 0..maze.height() as isize {
+        for i in 0..maze.width() as isize {
+            if pred(maze.cell(i, j)) {
+                out.push((i, j));
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn maze_issues(rows: &[&str]) -> Vec<String> {
+        check_maze(&Maze::parse(&rows.join("\n"), 64)).iter().map(|i| i.to_string()).collect()
+    }
+
+    fn has(issues: &[String], prefix: &str, text: &str) -> bool {
+        issues.iter().any(|i| i.starts_with(prefix) && i.contains(text))
+    }
+
+    #[test]
+    fn good_maze_has_no_issues() {
+        assert!(maze_issues(&["#####", "#P D#", "#####"]).is_empty());
+    }
+
+    #[test]
+    fn missing_spawn() {
+        let issues = maze_issues(&["#####", "#  D#", "#####"]);
+        assert!(has(&issues, "error", "falta 'P'"));
+        assert_eq!(issues.len(), 1); // (1,1) está libre: la puerta se alcanza desde ahí
+    }
+
+    #[test]
+    fn duplicate_spawn() {
+        let issues = maze_issues(&["#####", "#P PD", "#####"]);
+        assert!(has(&issues, "error", "hay 2 'P'"));
+    }
+
+    #[test]
+    fn default_spawn_inside_a_wall() {
+        let issues = maze_issues(&["#####", "## D#", "#####"]);
+        assert!(has(&issues, "error", "dentro de una pared"));
+    }
+
+    #[test]
+    fn missing_door() {
+        let issues = maze_issues(&["#####", "#P  #", "#####"]);
+        assert!(has(&issues, "error", "no hay puerta"));
+    }
+
+    #[test]
+    fn unreachable_doors() {
+        let issues = maze_issues(&["######", "#P#D #", "######"]);
+        assert!(has(&issues, "error", "ninguna puerta es alcanzable"));
+
+        // con otra puerta alcanzable, la encerrada es solo un aviso
+        let issues = maze_issues(&["#######", "#PD#D #", "#######"]);
+        assert_eq!(issues, ["aviso: la puerta (4, 1) no es alcanzable"]);
+    }
+
+    #[test]
+    fn ragged_rows_are_errors() {
+        let issues = check_grid_text("#####\n#P D#\n###", 3);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert!(issues[0].msg.starts_with("línea 5: 3 columnas en vez de 5"));
+    }
+
+    #[test]
+    fn unknown_characters_are_errors() {
+        let issues = check_grid_text("#####\n#P?D#\n#####", 1);
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Error);
+        assert!(issues[0].msg.starts_with("línea 2, columna 3: carácter desconocido '?'"));
+    }
+}